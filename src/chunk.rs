@@ -1,8 +1,13 @@
 use crate::{
     chunk_type::ChunkType,
+    error::{CrcMismatch, InternalError},
     types::{assert_or_err, error_from, Error, Result},
 };
-use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    hash::{Hash, Hasher},
+    io::{Read, Write},
+};
 
 // fixed length field widths
 pub const LENGTH_WIDTH: usize = 4;
@@ -10,22 +15,74 @@ pub const TYPE_WIDTH: usize = 4;
 pub const CRC_WIDTH: usize = 4;
 pub const REQ_FIELDS_WIDTH: usize = LENGTH_WIDTH + TYPE_WIDTH + CRC_WIDTH;
 
+// the spec limits chunk lengths to 2^31 - 1
+pub const MAX_CHUNK_LENGTH: u32 = (1 << 31) - 1;
+
+// size of the buffer used when streaming chunk data
+pub const STREAM_BUFFER_SIZE: usize = 8 * 1024;
+
+// the CRC algorithm used by PNG
+static CHUNK_CRC: crc::Crc<u32> = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
 /// Stores a PNG chunk
 #[derive(Debug)]
 pub struct Chunk {
     chunk_type: ChunkType,
     data: Vec<u8>,
+    // length and checksum fields that override the computed ones, only ever
+    // set by `new_unchecked`
+    forced_length: Option<u32>,
+    forced_crc: Option<u32>,
+    // checksum as it was read, if this chunk was parsed from bytes
+    stored_crc: Option<u32>,
 }
 
 impl Chunk {
-    /// Create a new chunk from a type and associated data
+    /// Create a new chunk from a type and associated data, panicking if there
+    /// is more data than a chunk can hold (`MAX_CHUNK_LENGTH`); see `try_new`
     pub fn new(chunk_type: ChunkType, data: Vec<u8>) -> Chunk {
-        Chunk { chunk_type, data }
+        Chunk::try_new(chunk_type, data).expect("chunk data too long")
+    }
+
+    /// Create a new chunk from a type and associated data, erroring if there
+    /// is more data than a chunk can hold (`MAX_CHUNK_LENGTH`)
+    pub fn try_new(chunk_type: ChunkType, data: Vec<u8>) -> Result<Chunk> {
+        check_length(data.len() as u64)?;
+        Ok(Chunk {
+            chunk_type,
+            data,
+            forced_length: None,
+            forced_crc: None,
+            stored_crc: None,
+        })
+    }
+
+    /// Create the (always empty) IEND chunk that marks the end of a PNG
+    pub fn new_iend() -> Chunk {
+        let chunk_type = ChunkType::try_from(*b"IEND").expect("IEND is a valid chunk type");
+        Chunk::new(chunk_type, Vec::new())
+    }
+
+    /// Create a chunk with the given length and checksum fields stored
+    /// verbatim, without checking them against the data.
+    ///
+    /// DANGER: `length`, `crc` and `as_bytes` report the stored values, so
+    /// the chunk can serialize to bytes that no PNG parser (including this
+    /// one) accepts. This only exists to test error handling paths.
+    #[cfg(any(test, feature = "unchecked"))]
+    pub fn new_unchecked(length: u32, chunk_type: ChunkType, data: Vec<u8>, crc: u32) -> Chunk {
+        Chunk {
+            chunk_type,
+            data,
+            forced_length: Some(length),
+            forced_crc: Some(crc),
+            stored_crc: None,
+        }
     }
 
     /// Get the length of the data portion of this chunk
     pub fn length(&self) -> u32 {
-        self.data.len() as u32
+        self.forced_length.unwrap_or(self.data.len() as u32)
     }
 
     /// Get the type of this chunk
@@ -33,20 +90,210 @@ impl Chunk {
         &self.chunk_type
     }
 
+    /// Tests whether this chunk is critical, from its type
+    pub fn is_critical(&self) -> bool {
+        self.chunk_type.is_critical()
+    }
+
     /// Get the data portion associated with this chunk
     pub fn data(&self) -> &[u8] {
         &self.data
     }
 
+    /// Create a new chunk with a copy of this chunk's data under another type
+    pub fn clone_with_type(&self, new_type: ChunkType) -> Chunk {
+        Chunk::new(new_type, self.data.clone())
+    }
+
+    /// Tests whether the data portion of this chunk starts with `needle`
+    pub fn data_starts_with(&self, needle: &[u8]) -> bool {
+        self.data.starts_with(needle)
+    }
+
+    /// Find the first position of `needle` in the data portion of this chunk
+    pub fn data_find(&self, needle: &[u8]) -> Option<usize> {
+        if needle.is_empty() {
+            return Some(0);
+        }
+        self.data.windows(needle.len()).position(|w| w == needle)
+    }
+
+    /// Calculate the Shannon entropy of the data portion in bits per byte,
+    /// from 0 (a single repeated byte) up to 8 (uniformly random bytes)
+    pub fn data_entropy(&self) -> f64 {
+        if self.data.is_empty() {
+            return 0.0;
+        }
+        let mut counts = [0usize; 256];
+        for &byte in &self.data {
+            counts[byte as usize] += 1;
+        }
+        let total = self.data.len() as f64;
+        counts
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = count as f64 / total;
+                -p * p.log2()
+            })
+            .sum()
+    }
+
+    /// Get mutable access to the data portion of this chunk. Any checksum the
+    /// chunk was parsed with is left as is until `refresh_crc` is called
+    pub fn data_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.data
+    }
+
+    /// Recompute the checksum from the current type and data and store it as
+    /// this chunk's checksum, returning the new value
+    pub fn refresh_crc(&mut self) -> u32 {
+        self.forced_crc = None;
+        let crc = self.crc();
+        self.stored_crc = Some(crc);
+        crc
+    }
+
+    /// Make the length field match the data again, undoing any length forced
+    /// by `new_unchecked`, and return the new length
+    pub fn refresh_length(&mut self) -> u32 {
+        self.forced_length = None;
+        self.length()
+    }
+
+    /// Get the checksum this chunk was parsed with, which for chunks parsed
+    /// without verification may not match `crc`
+    pub fn stored_crc(&self) -> Option<u32> {
+        self.stored_crc
+    }
+
     /// Calculate the checksum of this chunk based on its type and data portion
     pub fn crc(&self) -> u32 {
-        let crc = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+        self.forced_crc.unwrap_or_else(|| self.computed_crc())
+    }
 
-        let mut bytes = Vec::with_capacity((self.length() as usize) + TYPE_WIDTH);
-        bytes.extend(self.chunk_type.bytes());
-        bytes.extend(self.data());
+    /// Tests whether the checksum this chunk was parsed with, or the one
+    /// forced by `new_unchecked`, no longer matches its type and data
+    pub fn has_stale_crc(&self) -> bool {
+        let computed = self.computed_crc();
+        self.forced_crc
+            .into_iter()
+            .chain(self.stored_crc)
+            .any(|crc| crc != computed)
+    }
+
+    /// Calculate the checksum from the type and data, ignoring any forced one
+    fn computed_crc(&self) -> u32 {
+        let mut digest = CHUNK_CRC.digest();
+        digest.update(self.chunk_type.as_bytes());
+        digest.update(self.data());
+        digest.finalize()
+    }
 
-        crc.checksum(&bytes)
+    /// Read and verify the next chunk from a reader, returning None if the
+    /// reader is already at its end
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Option<Chunk>> {
+        let mut length_bytes = [0u8; LENGTH_WIDTH];
+        let read = reader.read(&mut length_bytes)?;
+        if read == 0 {
+            return Ok(None);
+        }
+        reader.read_exact(&mut length_bytes[read..])?;
+        let chunk_length = u64::from(u32::from_be_bytes(length_bytes));
+        check_length(chunk_length)?;
+
+        // read through `take` so a bogus length can't force a huge allocation
+        // before the data actually arrives
+        let mut bytes = length_bytes.to_vec();
+        let rest = chunk_length + (TYPE_WIDTH + CRC_WIDTH) as u64;
+        reader.by_ref().take(rest).read_to_end(&mut bytes)?;
+        assert_or_err(
+            bytes.len() as u64 == rest + LENGTH_WIDTH as u64,
+            "invalid png (incomplete chunk)",
+        )?;
+        Ok(Some(Chunk::try_from(bytes.as_slice())?))
+    }
+
+    /// Read and verify the next chunk from an async reader, returning None if
+    /// the reader is already at its end
+    #[cfg(feature = "async")]
+    pub async fn read_from_async<R>(reader: &mut R) -> Result<Option<Chunk>>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        use tokio::io::AsyncReadExt;
+
+        let mut length_bytes = [0u8; LENGTH_WIDTH];
+        let read = reader.read(&mut length_bytes).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        reader.read_exact(&mut length_bytes[read..]).await?;
+        let chunk_length = u64::from(u32::from_be_bytes(length_bytes));
+        check_length(chunk_length)?;
+
+        let mut bytes = length_bytes.to_vec();
+        let rest = chunk_length + (TYPE_WIDTH + CRC_WIDTH) as u64;
+        (&mut *reader).take(rest).read_to_end(&mut bytes).await?;
+        assert_or_err(
+            bytes.len() as u64 == rest + LENGTH_WIDTH as u64,
+            "invalid png (incomplete chunk)",
+        )?;
+        Ok(Some(Chunk::try_from(bytes.as_slice())?))
+    }
+
+    /// Find which common CRC-32 variant gives `stored_crc` for this chunk's
+    /// type and data, to tell a chunk written with the wrong algorithm apart
+    /// from a corrupt one
+    pub fn detect_crc_algorithm(&self, stored_crc: u32) -> Option<&'static str> {
+        const VARIANTS: [(&crc::Algorithm<u32>, &str); 6] = [
+            (&crc::CRC_32_ISO_HDLC, "CRC-32/ISO-HDLC"),
+            (&crc::CRC_32_BZIP2, "CRC-32/BZIP2"),
+            (&crc::CRC_32_MPEG_2, "CRC-32/MPEG-2"),
+            (&crc::CRC_32_CKSUM, "CRC-32/CKSUM"),
+            (&crc::CRC_32_ISCSI, "CRC-32/ISCSI"),
+            (&crc::CRC_32_JAMCRC, "CRC-32/JAMCRC"),
+        ];
+        VARIANTS
+            .iter()
+            .find(|(algorithm, _)| {
+                let crc = crc::Crc::<u32>::new(algorithm);
+                let mut digest = crc.digest();
+                digest.update(self.chunk_type.as_bytes());
+                digest.update(&self.data);
+                digest.finalize() == stored_crc
+            })
+            .map(|&(_, name)| name)
+    }
+
+    /// Read a chunk from a reader and check its checksum without keeping its
+    /// data in memory, returning the type of the chunk
+    pub fn verify_from_reader<R: Read>(reader: &mut R) -> Result<ChunkType> {
+        let mut length_bytes = [0u8; LENGTH_WIDTH];
+        reader.read_exact(&mut length_bytes)?;
+        let mut type_bytes = [0u8; TYPE_WIDTH];
+        reader.read_exact(&mut type_bytes)?;
+        let chunk_type = ChunkType::try_from(type_bytes)?;
+
+        // feed the data through the checksum a buffer at a time
+        let mut digest = CHUNK_CRC.digest();
+        digest.update(&type_bytes);
+        let mut remaining = u32::from_be_bytes(length_bytes) as usize;
+        let mut buff = [0u8; STREAM_BUFFER_SIZE];
+        while remaining > 0 {
+            let want = remaining.min(STREAM_BUFFER_SIZE);
+            let read = reader.read(&mut buff[..want])?;
+            assert_or_err(read > 0, "invalid chunk data (incomplete)")?;
+            digest.update(&buff[..read]);
+            remaining -= read;
+        }
+
+        let mut crc_bytes = [0u8; CRC_WIDTH];
+        reader.read_exact(&mut crc_bytes)?;
+        if digest.finalize() != u32::from_be_bytes(crc_bytes) {
+            return Err(CrcMismatch.into());
+        }
+        Ok(chunk_type)
     }
 
     /// Try to read data as UTF8
@@ -57,22 +304,196 @@ impl Chunk {
         }
     }
 
-    /// Get this entire chunk as a vector of raw bytes
+    /// Get the length and type fields that precede the data portion
+    pub fn header_bytes(&self) -> [u8; LENGTH_WIDTH + TYPE_WIDTH] {
+        let mut bytes = [0u8; LENGTH_WIDTH + TYPE_WIDTH];
+        bytes[..LENGTH_WIDTH].copy_from_slice(&self.length().to_be_bytes());
+        bytes[LENGTH_WIDTH..].copy_from_slice(self.chunk_type.as_bytes());
+        bytes
+    }
+
+    /// Get the checksum field that follows the data portion
+    pub fn crc_bytes(&self) -> [u8; CRC_WIDTH] {
+        self.crc().to_be_bytes()
+    }
+
+    /// Get the number of bytes this entire chunk takes up when serialized
+    pub fn total_len(&self) -> usize {
+        REQ_FIELDS_WIDTH + self.data.len()
+    }
+
+    /// Write this entire chunk into the start of `dst`, like `as_bytes` but
+    /// without allocating, returning how many bytes were written
+    pub fn write_to_slice(&self, dst: &mut [u8]) -> Result<usize> {
+        let len = self.total_len();
+        let available = dst.len();
+        let dst = dst.get_mut(..len).ok_or_else(|| {
+            error_from(&format!(
+                "buffer too small for chunk: need {} bytes, have {}",
+                len, available
+            ))
+        })?;
+        let data_begin = LENGTH_WIDTH + TYPE_WIDTH;
+        let crc_begin = data_begin + self.data.len();
+        dst[..data_begin].copy_from_slice(&self.header_bytes());
+        dst[data_begin..crc_begin].copy_from_slice(&self.data);
+        dst[crc_begin..].copy_from_slice(&self.crc_bytes());
+        Ok(len)
+    }
+
+    /// Write this entire chunk to a writer, like `as_bytes` but without
+    /// copying the data into a new buffer first
+    pub fn write_to<W: Write>(&self, mut w: W) -> Result<()> {
+        w.write_all(&self.header_bytes())?;
+        w.write_all(&self.data)?;
+        w.write_all(&self.crc_bytes())?;
+        Ok(())
+    }
+
+    /// Write this entire chunk to an async writer
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W>(&self, w: &mut W) -> Result<()>
+    where
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        use tokio::io::AsyncWriteExt;
+
+        w.write_all(&self.header_bytes()).await?;
+        w.write_all(&self.data).await?;
+        w.write_all(&self.crc_bytes()).await?;
+        Ok(())
+    }
+
+    /// Get this entire chunk as a vector of raw bytes, with the checksum
+    /// calculated from the current data (fixing any corrupt checksum)
     pub fn as_bytes(&self) -> Vec<u8> {
         // I could use iterators here, but I like this better - it feels simpler to me
-        let mut bytes = Vec::with_capacity(REQ_FIELDS_WIDTH + (self.length() as usize));
-        bytes.extend(self.length().to_be_bytes());
-        bytes.extend(self.chunk_type.bytes());
+        let mut bytes = Vec::with_capacity(self.total_len());
+        bytes.extend(self.header_bytes());
         bytes.extend(self.data());
-        bytes.extend(self.crc().to_be_bytes());
+        bytes.extend(self.crc_bytes());
+        bytes
+    }
+
+    /// Get this entire chunk as a vector of raw bytes, keeping the checksum
+    /// it was parsed with (even if it is corrupt)
+    pub fn as_bytes_preserving_crc(&self) -> Vec<u8> {
+        let mut bytes = self.as_bytes();
+        if let Some(crc) = self.stored_crc {
+            let crc_begin = bytes.len() - CRC_WIDTH;
+            bytes[crc_begin..].copy_from_slice(&crc.to_be_bytes());
+        }
         bytes
     }
+
+    /// Get this entire chunk as a base64 string
+    #[cfg(feature = "base64")]
+    pub fn to_base64(&self) -> String {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+        STANDARD.encode(self.as_bytes())
+    }
+
+    /// Parse a chunk from a base64 string of its raw bytes
+    #[cfg(feature = "base64")]
+    pub fn from_base64(s: &str) -> Result<Chunk> {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+        let bytes = STANDARD.decode(s.trim())?;
+        Chunk::try_from(bytes.as_slice())
+    }
+
+    /// Check that this chunk survives being serialized and parsed back,
+    /// panicking with a description of the first mismatch found
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn assert_round_trips(&self) {
+        let bytes = self.as_bytes();
+        assert_eq!(
+            bytes.len(),
+            REQ_FIELDS_WIDTH + self.data.len(),
+            "chunk {} serialized to the wrong number of bytes",
+            self
+        );
+        let parsed = match Chunk::try_from(bytes.as_ref()) {
+            Ok(parsed) => parsed,
+            Err(e) => panic!("chunk {} failed to parse back: {}", self, e),
+        };
+        assert_eq!(
+            &parsed, self,
+            "chunk {} parsed back as a different chunk",
+            self
+        );
+        assert_eq!(
+            parsed.as_bytes(),
+            bytes,
+            "chunk {} serialized differently after parsing",
+            self
+        );
+    }
+
+    /// Parse a chunk from raw bytes without checking its checksum, keeping
+    /// the checksum as read so it can be inspected or written back
+    pub fn try_from_unverified(value: &[u8]) -> Result<Chunk> {
+        Chunk::parse(value, false)
+    }
+
+    /// Parse a chunk from raw bytes, optionally checking its checksum
+    fn parse(value: &[u8], verify_crc: bool) -> Result<Chunk> {
+        let chunk_ref = ChunkRef::parse(value)?;
+        if verify_crc && chunk_ref.crc() != chunk_ref.stored_crc {
+            return Err(CrcMismatch.into());
+        }
+        Ok(chunk_ref.to_owned())
+    }
 }
 
-impl TryFrom<&[u8]> for Chunk {
-    type Error = Error;
-    /// Gives the ability to construct a Chunk from raw bytes
-    fn try_from(value: &[u8]) -> Result<Self> {
+/// A chunk borrowed from a buffer of raw bytes, for reading chunks without
+/// copying their data; `to_owned` gives a `Chunk` when a copy is needed
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ChunkRef<'a> {
+    chunk_type: ChunkType,
+    data: &'a [u8],
+    stored_crc: u32,
+}
+
+impl<'a> ChunkRef<'a> {
+    /// Get the length of the data portion of this chunk
+    pub fn length(&self) -> u32 {
+        self.data.len() as u32
+    }
+
+    /// Get the type of this chunk
+    pub fn chunk_type(&self) -> &ChunkType {
+        &self.chunk_type
+    }
+
+    /// Get the data portion of this chunk, borrowed from the original buffer
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Get the checksum this chunk was read with
+    pub fn stored_crc(&self) -> u32 {
+        self.stored_crc
+    }
+
+    /// Calculate the checksum of this chunk based on its type and data portion
+    pub fn crc(&self) -> u32 {
+        let mut digest = CHUNK_CRC.digest();
+        digest.update(self.chunk_type.as_bytes());
+        digest.update(self.data);
+        digest.finalize()
+    }
+
+    /// Copy this chunk's data into an owned Chunk
+    pub fn to_owned(self) -> Chunk {
+        let mut chunk = Chunk::new(self.chunk_type, self.data.to_vec());
+        chunk.stored_crc = Some(self.stored_crc);
+        chunk
+    }
+
+    /// Split raw chunk bytes into their fields, without checking the checksum
+    fn parse(value: &'a [u8]) -> Result<ChunkRef<'a>> {
         let length_begin: usize = 0;
         let type_begin: usize = length_begin + LENGTH_WIDTH;
         let data_begin: usize = type_begin + TYPE_WIDTH;
@@ -82,28 +503,148 @@ impl TryFrom<&[u8]> for Chunk {
             value.len() >= REQ_FIELDS_WIDTH, 
             "invalid chunk data (incomplete)",
         )?;
-        let chunk_length = u32::from_be_bytes(value[length_begin..type_begin].try_into()?);
+        let chunk_length = u32::from_be_bytes(read_field(value, length_begin, "length")?);
+        check_length(u64::from(chunk_length))?;
 
-        // make sure the slice length matches the indicated length
-        assert_or_err(
-            value.len() == REQ_FIELDS_WIDTH + (chunk_length as usize),
-            "invalid chunk data (invalid length)",
-        )?;
+        // make sure the slice length matches the indicated length, without
+        // trusting the length field enough to overflow or index with it
+        let expected_len = u64::from(chunk_length) + REQ_FIELDS_WIDTH as u64;
+        let actual_len = value.len() as u64;
+        if actual_len < expected_len {
+            return Err(error_from(&format!(
+                "chunk truncated: need {} bytes, have {}",
+                expected_len, actual_len
+            )));
+        }
+        if actual_len > expected_len {
+            return Err(error_from(&format!(
+                "trailing bytes after chunk: {} extra",
+                actual_len - expected_len
+            )));
+        }
         let crc_begin = data_begin + (chunk_length as usize);
 
         // read remaining fields
-        let chunk_type_bytes: [u8; 4] = value[type_begin..data_begin].try_into()?;
-        let chunk_type = ChunkType::try_from(chunk_type_bytes)?;
-        let chunk_data = value[data_begin..crc_begin].to_vec();
-        let chunk_crc_bytes: [u8; 4] = value[crc_begin..].try_into()?;
-        let chunk_crc = u32::from_be_bytes(chunk_crc_bytes);
-        // validate & return
-        let unchecked_chunk = Chunk::new(chunk_type, chunk_data);
-        assert_or_err(
-            unchecked_chunk.crc() == chunk_crc, 
-            "checksum does not match data",
-        )?;
-        Ok(unchecked_chunk)
+        let chunk_type = ChunkType::try_from(read_field(value, type_begin, "type")?)?;
+        let data = value
+            .get(data_begin..crc_begin)
+            .ok_or_else(|| error_from("invalid chunk data (invalid length)"))?;
+        let stored_crc = u32::from_be_bytes(read_field(value, crc_begin, "CRC")?);
+        Ok(ChunkRef {
+            chunk_type,
+            data,
+            stored_crc,
+        })
+    }
+}
+
+/// Error if a chunk's data length is over the spec's limit
+pub(crate) fn check_length(length: u64) -> Result<()> {
+    assert_or_err(
+        length <= u64::from(MAX_CHUNK_LENGTH),
+        &format!(
+            "chunk length {} exceeds the maximum of {}",
+            length, MAX_CHUNK_LENGTH
+        ),
+    )
+}
+
+/// Copy a 4 byte field out of raw chunk bytes. The length checks in `parse`
+/// mean this shouldn't fail, so a failure names the field to point at the bug
+fn read_field(value: &[u8], begin: usize, name: &str) -> Result<[u8; 4]> {
+    value
+        .get(begin..)
+        .and_then(|rest| rest.get(..4))
+        .and_then(|field| field.try_into().ok())
+        .ok_or_else(|| InternalError(format!("failed to read {} field", name)).into())
+}
+
+/// Builds a chunk from data supplied piece by piece, updating its checksum
+/// as each piece arrives
+pub struct ChunkBuilder {
+    chunk_type: ChunkType,
+    data: Vec<u8>,
+    digest: crc::Digest<'static, u32>,
+}
+
+impl ChunkBuilder {
+    /// Start building a chunk of the given type with no data
+    pub fn new(chunk_type: ChunkType) -> ChunkBuilder {
+        let mut digest = CHUNK_CRC.digest();
+        digest.update(chunk_type.as_bytes());
+        ChunkBuilder {
+            chunk_type,
+            data: Vec::new(),
+            digest,
+        }
+    }
+
+    /// Append bytes to the data portion of the chunk
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+        self.digest.update(bytes);
+    }
+
+    /// Finish the chunk, storing the checksum computed along the way
+    pub fn finish(self) -> Chunk {
+        let mut chunk = Chunk::new(self.chunk_type, self.data);
+        chunk.stored_crc = Some(self.digest.finalize());
+        chunk
+    }
+}
+
+impl From<(ChunkType, &[u8])> for Chunk {
+    /// Gives the ability to construct a Chunk from a type and borrowed data
+    fn from((chunk_type, data): (ChunkType, &[u8])) -> Self {
+        Chunk::new(chunk_type, data.to_vec())
+    }
+}
+
+impl From<(ChunkType, Vec<u8>)> for Chunk {
+    /// Gives the ability to construct a Chunk from a type and owned data
+    fn from((chunk_type, data): (ChunkType, Vec<u8>)) -> Self {
+        Chunk::new(chunk_type, data)
+    }
+}
+
+impl PartialEq for Chunk {
+    /// Chunks are equal when they serialize to the same bytes, regardless of
+    /// the checksum they were parsed with
+    fn eq(&self, other: &Self) -> bool {
+        self.chunk_type == other.chunk_type
+            && self.data == other.data
+            && self.forced_length == other.forced_length
+            && self.forced_crc == other.forced_crc
+    }
+}
+
+impl Eq for Chunk {}
+
+impl Hash for Chunk {
+    /// Hashes the type and data, so equal chunks hash the same
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.chunk_type.hash(state);
+        self.data.hash(state);
+    }
+}
+
+impl TryFrom<&[u8]> for Chunk {
+    type Error = Error;
+    /// Gives the ability to construct a Chunk from raw bytes
+    fn try_from(value: &[u8]) -> Result<Self> {
+        Chunk::parse(value, true)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for ChunkRef<'a> {
+    type Error = Error;
+    /// Gives the ability to view a chunk in raw bytes without copying it
+    fn try_from(value: &'a [u8]) -> Result<Self> {
+        let chunk_ref = ChunkRef::parse(value)?;
+        if chunk_ref.crc() != chunk_ref.stored_crc {
+            return Err(CrcMismatch.into());
+        }
+        Ok(chunk_ref)
     }
 }
 
@@ -170,6 +711,13 @@ mod tests {
         assert_eq!(chunk.chunk_type().to_string(), String::from("RuSt"));
     }
 
+    #[test]
+    fn test_chunk_is_critical() {
+        assert!(testing_chunk().is_critical());
+        let chunk = Chunk::new(ChunkType::from_str("ruSt").unwrap(), Vec::new());
+        assert!(!chunk.is_critical());
+    }
+
     #[test]
     fn test_chunk_string() {
         let chunk = testing_chunk();
@@ -252,4 +800,458 @@ mod tests {
 
         let _chunk_string = format!("{}", chunk);
     }
+
+    #[test]
+    fn test_new_iend() {
+        let chunk = Chunk::new_iend();
+        assert_eq!(&chunk.chunk_type().to_string(), "IEND");
+        assert_eq!(chunk.length(), 0);
+        assert_eq!(chunk.as_bytes(), [0, 0, 0, 0, 73, 69, 78, 68, 174, 66, 96, 130]);
+    }
+
+    #[test]
+    fn test_new_unchecked() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let data = b"This is where your secret message will be!".to_vec();
+        let chunk = Chunk::new_unchecked(7, chunk_type, data, 1234);
+        assert_eq!(chunk.length(), 7);
+        assert_eq!(chunk.crc(), 1234);
+
+        let bytes = chunk.as_bytes();
+        assert_eq!(bytes[..LENGTH_WIDTH], 7u32.to_be_bytes());
+        assert_eq!(bytes[bytes.len() - CRC_WIDTH..], 1234u32.to_be_bytes());
+        assert!(Chunk::try_from(bytes.as_ref()).is_err());
+    }
+
+    #[test]
+    fn test_new_unchecked_bad_crc_only() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let data = b"This is where your secret message will be!".to_vec();
+        let chunk = Chunk::new_unchecked(42, chunk_type, data, 2882656333);
+        let result = Chunk::try_from(chunk.as_bytes().as_ref());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "checksum does not match data"
+        );
+    }
+
+    #[test]
+    fn test_try_from_unverified() {
+        let mut bytes = testing_chunk().as_bytes();
+        let crc_begin = bytes.len() - CRC_WIDTH;
+        bytes[crc_begin..].copy_from_slice(&1234u32.to_be_bytes());
+        assert!(Chunk::try_from(bytes.as_ref()).is_err());
+
+        let chunk = Chunk::try_from_unverified(bytes.as_ref()).unwrap();
+        assert_eq!(chunk.stored_crc(), Some(1234));
+        assert_eq!(chunk.crc(), 2882656334);
+        assert_eq!(chunk, testing_chunk());
+    }
+
+    #[test]
+    fn test_as_bytes_crc_modes() {
+        let mut bytes = testing_chunk().as_bytes();
+        let crc_begin = bytes.len() - CRC_WIDTH;
+        bytes[crc_begin..].copy_from_slice(&1234u32.to_be_bytes());
+        let chunk = Chunk::try_from_unverified(bytes.as_ref()).unwrap();
+
+        assert_eq!(chunk.as_bytes_preserving_crc(), bytes);
+        assert_eq!(chunk.as_bytes(), testing_chunk().as_bytes());
+    }
+
+    #[test]
+    fn test_chunk_builder() {
+        let whole = testing_chunk();
+        let mut builder = ChunkBuilder::new(*whole.chunk_type());
+        for piece in whole.data().chunks(5) {
+            builder.push(piece);
+        }
+        let built = builder.finish();
+        assert_eq!(built, whole);
+        assert_eq!(built.stored_crc(), Some(whole.crc()));
+        assert_eq!(built.as_bytes_preserving_crc(), whole.as_bytes());
+    }
+
+    #[test]
+    fn test_chunk_length_mismatch_messages() {
+        let bytes = testing_chunk().as_bytes();
+        let err = Chunk::try_from(&bytes[..bytes.len() - 3]).unwrap_err();
+        assert_eq!(err.to_string(), "chunk truncated: need 54 bytes, have 51");
+
+        let mut long = bytes.clone();
+        long.extend([0, 0]);
+        let err = Chunk::try_from(long.as_ref()).unwrap_err();
+        assert_eq!(err.to_string(), "trailing bytes after chunk: 2 extra");
+    }
+
+    #[test]
+    fn test_clone_with_type() {
+        let chunk = testing_chunk();
+        let clone = chunk.clone_with_type(ChunkType::from_str("ruSt").unwrap());
+        assert_eq!(&clone.chunk_type().to_string(), "ruSt");
+        assert_eq!(clone.data(), chunk.data());
+        assert_ne!(clone.crc(), chunk.crc());
+        assert_eq!(Chunk::try_from(clone.as_bytes().as_ref()).unwrap(), clone);
+    }
+
+    #[test]
+    fn test_data_search() {
+        let mut data = vec![0xff, 0xd8, 0xff, 0xe0];
+        data.extend(b"JFIF\0");
+        let chunk = Chunk::new(ChunkType::from_str("emBd").unwrap(), data);
+        assert!(chunk.data_starts_with(&[0xff, 0xd8, 0xff]));
+        assert!(!chunk.data_starts_with(b"JFIF"));
+        assert_eq!(chunk.data_find(b"JFIF"), Some(4));
+        assert_eq!(chunk.data_find(b"Exif"), None);
+        assert_eq!(chunk.data_find(b""), Some(0));
+    }
+
+    #[test]
+    fn test_read_field_errors_name_the_field() {
+        use crate::error::{error_kind, ErrorKind};
+        let bytes = testing_chunk().as_bytes();
+        assert_eq!(read_field(&bytes, 4, "type").unwrap(), *b"RuSt");
+        let err = read_field(&bytes[..6], 4, "type").unwrap_err();
+        assert_eq!(err.to_string(), "failed to read type field");
+        let err = read_field(&bytes, bytes.len(), "CRC").unwrap_err();
+        assert_eq!(err.to_string(), "failed to read CRC field");
+        assert_eq!(error_kind(&err), ErrorKind::Internal);
+    }
+
+    #[test]
+    fn test_data_entropy() {
+        let chunk_type = ChunkType::from_str("raNd").unwrap();
+        let mut state: u64 = 0x2545F4914F6CDD1D;
+        let random: Vec<u8> = (0..1 << 16)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state >> 32) as u8
+            })
+            .collect();
+        let entropy = Chunk::new(chunk_type, random).data_entropy();
+        assert!(entropy > 7.99 && entropy <= 8.0, "{}", entropy);
+
+        assert_eq!(Chunk::new(chunk_type, vec![0; 1024]).data_entropy(), 0.0);
+        assert_eq!(Chunk::new(chunk_type, Vec::new()).data_entropy(), 0.0);
+        assert_eq!(Chunk::new(chunk_type, b"abab".to_vec()).data_entropy(), 1.0);
+    }
+
+    #[test]
+    fn test_write_to_slice() {
+        let first = testing_chunk();
+        let second = Chunk::new_iend();
+        let mut buff = vec![0u8; first.total_len() + second.total_len()];
+        let written = first.write_to_slice(&mut buff).unwrap();
+        assert_eq!(written, 54);
+        second.write_to_slice(&mut buff[written..]).unwrap();
+
+        let mut expected = first.as_bytes();
+        expected.extend(second.as_bytes());
+        assert_eq!(buff, expected);
+    }
+
+    #[test]
+    fn test_write_to() {
+        let mut out: Vec<u8> = Vec::new();
+        testing_chunk().write_to(&mut out).unwrap();
+        Chunk::new_iend().write_to(&mut out).unwrap();
+        let mut expected = testing_chunk().as_bytes();
+        expected.extend(Chunk::new_iend().as_bytes());
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_write_to_slice_too_small() {
+        let mut buff = [0u8; 20];
+        let err = testing_chunk().write_to_slice(&mut buff).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "buffer too small for chunk: need 54 bytes, have 20"
+        );
+    }
+
+    #[test]
+    fn test_detect_crc_algorithm() {
+        let chunk = testing_chunk();
+        let bzip2 = crc::Crc::<u32>::new(&crc::CRC_32_BZIP2);
+        let mut digest = bzip2.digest();
+        digest.update(b"RuSt");
+        digest.update(chunk.data());
+        let wrong_crc = digest.finalize();
+
+        let mut bytes = chunk.as_bytes();
+        let crc_begin = bytes.len() - CRC_WIDTH;
+        bytes[crc_begin..].copy_from_slice(&wrong_crc.to_be_bytes());
+        let parsed = Chunk::try_from_unverified(&bytes).unwrap();
+        let stored = parsed.stored_crc().unwrap();
+        assert_eq!(parsed.detect_crc_algorithm(stored), Some("CRC-32/BZIP2"));
+
+        assert_eq!(chunk.detect_crc_algorithm(chunk.crc()), Some("CRC-32/ISO-HDLC"));
+        assert_eq!(chunk.detect_crc_algorithm(1234), None);
+    }
+
+    #[test]
+    fn test_max_chunk_length() {
+        assert!(check_length(u64::from(MAX_CHUNK_LENGTH)).is_ok());
+        assert_eq!(
+            check_length(u64::from(MAX_CHUNK_LENGTH) + 1).unwrap_err().to_string(),
+            "chunk length 2147483648 exceeds the maximum of 2147483647"
+        );
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        assert!(Chunk::try_new(chunk_type, b"data".to_vec()).is_ok());
+
+        // a length with the high bit set is rejected before the data is read
+        let mut bytes = 0x8000_0000u32.to_be_bytes().to_vec();
+        bytes.extend(b"RuSt");
+        bytes.extend([0; CRC_WIDTH]);
+        let err = Chunk::try_from(bytes.as_slice()).unwrap_err();
+        assert!(err.to_string().starts_with("chunk length 2147483648 exceeds"));
+        assert!(Chunk::read_from(&mut bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_chunk_ref() {
+        let bytes = testing_chunk().as_bytes();
+        let chunk_ref = ChunkRef::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(chunk_ref.length(), 42);
+        assert_eq!(&chunk_ref.chunk_type().to_string(), "RuSt");
+        assert_eq!(chunk_ref.data(), &bytes[8..50]);
+        assert_eq!(chunk_ref.crc(), 2882656334);
+        assert_eq!(chunk_ref.stored_crc(), 2882656334);
+        assert_eq!(chunk_ref.to_owned(), testing_chunk());
+
+        let mut corrupt = bytes.clone();
+        corrupt[50] ^= 1;
+        assert!(crate::error::is_crc_mismatch(
+            &ChunkRef::try_from(corrupt.as_slice()).unwrap_err()
+        ));
+        assert!(ChunkRef::try_from(&bytes[..20]).is_err());
+    }
+
+    #[test]
+    fn test_read_from() {
+        let mut bytes = testing_chunk().as_bytes();
+        bytes.extend(Chunk::new_iend().as_bytes());
+        let mut reader = bytes.as_slice();
+        assert_eq!(Chunk::read_from(&mut reader).unwrap(), Some(testing_chunk()));
+        assert_eq!(Chunk::read_from(&mut reader).unwrap(), Some(Chunk::new_iend()));
+        assert_eq!(Chunk::read_from(&mut reader).unwrap(), None);
+
+        let mut truncated = &bytes[..20];
+        assert!(Chunk::read_from(&mut truncated).is_err());
+    }
+
+    #[test]
+    fn test_refresh_length() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let mut chunk = Chunk::new_unchecked(7, chunk_type, b"data".to_vec(), 1234);
+        assert_eq!(chunk.length(), 7);
+        assert_eq!(chunk.refresh_length(), 4);
+        assert_eq!(chunk.length(), 4);
+        assert_eq!(chunk.crc(), 1234);
+    }
+
+    #[test]
+    fn test_refresh_crc() {
+        let mut chunk = Chunk::try_from(testing_chunk().as_bytes().as_ref()).unwrap();
+        let old_crc = chunk.crc();
+        chunk.data_mut().extend(b" Indeed.");
+        assert_eq!(chunk.stored_crc(), Some(old_crc));
+
+        let new_crc = chunk.refresh_crc();
+        assert_ne!(new_crc, old_crc);
+        assert_eq!(chunk.crc(), new_crc);
+        assert_eq!(chunk.stored_crc(), Some(new_crc));
+        assert_eq!(chunk.as_bytes_preserving_crc(), chunk.as_bytes());
+    }
+
+    #[test]
+    fn test_has_stale_crc() {
+        assert!(!testing_chunk().has_stale_crc());
+
+        let mut chunk = Chunk::try_from(testing_chunk().as_bytes().as_ref()).unwrap();
+        assert!(!chunk.has_stale_crc());
+        chunk.data_mut().push(b'!');
+        assert!(chunk.has_stale_crc());
+        chunk.refresh_crc();
+        assert!(!chunk.has_stale_crc());
+
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let mut forced = Chunk::new_unchecked(4, chunk_type, b"note".to_vec(), 1234);
+        assert!(forced.has_stale_crc());
+        forced.refresh_crc();
+        assert!(!forced.has_stale_crc());
+    }
+
+    #[test]
+    fn test_random_chunks_round_trip() {
+        // small xorshift generator so the test is repeatable
+        let mut state: u64 = 0x2545F4914F6CDD1D;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for _ in 0..200 {
+            let type_bytes = [0; 4].map(|_| {
+                let letter = b'a' + (next() % 26) as u8;
+                match next() % 2 {
+                    0 => letter,
+                    _ => letter.to_ascii_uppercase(),
+                }
+            });
+            let data: Vec<u8> = (0..next() % 512).map(|_| next() as u8).collect();
+            let chunk = Chunk::new(ChunkType::try_from(type_bytes).unwrap(), data);
+            chunk.assert_round_trips();
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "failed to parse back")]
+    fn test_unchecked_chunk_does_not_round_trip() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        Chunk::new_unchecked(0, chunk_type, Vec::new(), 0).assert_round_trips();
+    }
+
+    #[test]
+    fn test_chunk_from_tuples() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let message = "This is where your secret message will be!".as_bytes();
+
+        let borrowed = Chunk::from((chunk_type, message));
+        assert_eq!(borrowed, testing_chunk());
+
+        let owned: Chunk = (chunk_type, message.to_vec()).into();
+        assert_eq!(owned, testing_chunk());
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn test_chunk_base64_round_trip() {
+        let chunk = testing_chunk();
+        let encoded = chunk.to_base64();
+        assert!(encoded.starts_with("AAAAKlJ1U3"));
+        assert_eq!(Chunk::from_base64(&encoded).unwrap(), chunk);
+        assert!(Chunk::from_base64("not base64!").is_err());
+    }
+
+    #[test]
+    fn test_chunk_hash_dedup() {
+        use std::collections::HashSet;
+
+        let mut set = HashSet::new();
+        assert!(set.insert(testing_chunk()));
+        assert!(!set.insert(testing_chunk()));
+        assert!(set.insert(Chunk::new_iend()));
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_chunk_byte_regions() {
+        let chunk = testing_chunk();
+        let bytes: Vec<u8> = chunk
+            .header_bytes()
+            .iter()
+            .chain(chunk.data())
+            .chain(chunk.crc_bytes().iter())
+            .copied()
+            .collect();
+        assert_eq!(bytes, chunk.as_bytes());
+    }
+
+    #[test]
+    fn test_chunk_write_vectored() {
+        use std::io::{IoSlice, Write};
+
+        let chunk = testing_chunk();
+        let header = chunk.header_bytes();
+        let crc = chunk.crc_bytes();
+        let slices = [
+            IoSlice::new(&header),
+            IoSlice::new(chunk.data()),
+            IoSlice::new(&crc),
+        ];
+        let mut out: Vec<u8> = Vec::new();
+        let written = out.write_vectored(&slices).unwrap();
+        assert_eq!(written, out.len());
+        assert_eq!(out, chunk.as_bytes());
+    }
+
+    /// Reader that remembers the largest read it was asked for
+    struct TrackingReader<R: Read> {
+        inner: R,
+        largest_read: usize,
+    }
+
+    impl<R: Read> Read for TrackingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.largest_read = self.largest_read.max(buf.len());
+            self.inner.read(buf)
+        }
+    }
+
+    #[test]
+    fn test_verify_from_reader() {
+        let data = vec![0xA5u8; 4 * 1024 * 1024];
+        let chunk = Chunk::new(ChunkType::from_str("RuSt").unwrap(), data);
+        let mut reader = TrackingReader {
+            inner: std::io::Cursor::new(chunk.as_bytes()),
+            largest_read: 0,
+        };
+
+        let chunk_type = Chunk::verify_from_reader(&mut reader).unwrap();
+
+        assert_eq!(&chunk_type.to_string(), "RuSt");
+        assert!(reader.largest_read <= STREAM_BUFFER_SIZE);
+    }
+
+    #[test]
+    fn test_verify_from_reader_bad_crc() {
+        let mut bytes = testing_chunk().as_bytes();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert!(Chunk::verify_from_reader(&mut bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_verify_from_reader_truncated() {
+        let bytes = testing_chunk().as_bytes();
+        let truncated = &bytes[..bytes.len() - CRC_WIDTH - 1];
+        assert!(Chunk::verify_from_reader(&mut &truncated[..]).is_err());
+    }
+
+    #[test]
+    fn test_truncated_chunks_never_panic() {
+        let chunk_bytes = testing_chunk().as_bytes();
+        for end in 0..chunk_bytes.len() {
+            assert!(Chunk::try_from(&chunk_bytes[..end]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_malformed_chunks_never_panic() {
+        let chunk_bytes = testing_chunk().as_bytes();
+        let lengths = [0u32, 1, 41, 43, 0x7FFF_FFFF, 0xFFFF_FFF3, u32::MAX];
+        for length in lengths {
+            let mut bad = chunk_bytes.clone();
+            bad[..LENGTH_WIDTH].copy_from_slice(&length.to_be_bytes());
+            assert!(Chunk::try_from(bad.as_ref()).is_err());
+        }
+
+        // flip every bit of the chunk in turn
+        for i in 0..chunk_bytes.len() * 8 {
+            let mut bad = chunk_bytes.clone();
+            bad[i / 8] ^= 1 << (i % 8);
+            assert!(Chunk::try_from(bad.as_ref()).is_err());
+        }
+
+        // appending extra bytes is also an error
+        let mut bad = chunk_bytes;
+        bad.extend([0u8; 16]);
+        assert!(Chunk::try_from(bad.as_ref()).is_err());
+    }
 }
@@ -1,27 +1,38 @@
 use crate::types::{error_from, Error, Result};
 use std::{
+    borrow::Borrow,
     fmt::{Display, Formatter, Result as FmtResult},
     str::FromStr,
 };
 
+/// Copy rules for a chunk type, per the PNG spec's safe-to-copy bit
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum CopySemantics {
+    /// Ancillary chunk that depends on the image data, so must be dropped
+    /// if the critical chunks were modified
+    MustNotCopyWithModifiedData,
+    /// Ancillary chunk that may be copied regardless of modifications
+    SafeToCopy,
+    /// Critical chunk, for which the copy bit has no meaning
+    CriticalNotApplicable,
+}
+
 /// Represents a PNG chunk type code
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Debug)]
 pub struct ChunkType {
-    ancillary: u8,
-    private: u8,
-    reserved: u8,
-    safe_to_copy: u8,
+    // in order: the ancillary, private, reserved and safe-to-copy bytes
+    bytes: [u8; 4],
 }
 
 impl ChunkType {
     /// Returns the raw chunk type bytes
     pub fn bytes(&self) -> [u8; 4] {
-        [
-            self.ancillary,
-            self.private,
-            self.reserved,
-            self.safe_to_copy,
-        ]
+        self.bytes
+    }
+
+    /// Borrow the raw chunk type bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
     }
 
     /// Tests chunk type validity
@@ -31,22 +42,158 @@ impl ChunkType {
 
     /// Tests chunk type ancillary bit (byte 0 bit 5)
     pub fn is_critical(&self) -> bool {
-        self.ancillary & 32u8 == 0u8
+        self.bytes[0] & 32u8 == 0u8
     }
 
     /// Tests chunk type private bit (byte 1 bit 5)
     pub fn is_public(&self) -> bool {
-        self.private & 32u8 == 0u8
+        self.bytes[1] & 32u8 == 0u8
     }
 
     /// Tests chunk type reserved bit validity (byte 2 bit 5)
     pub fn is_reserved_bit_valid(&self) -> bool {
-        self.reserved & 32u8 == 0u8
+        self.bytes[2] & 32u8 == 0u8
     }
 
     /// Tests chunk type copy bit (byte 3 bit 5)
+    ///
+    /// The copy bit only has meaning for ancillary chunks, see
+    /// `copy_semantics` for a check that accounts for this
     pub fn is_safe_to_copy(&self) -> bool {
-        self.safe_to_copy & 32u8 != 0u8
+        self.bytes[3] & 32u8 != 0u8
+    }
+
+    /// Get the four property bits as one value, where bit `i` is set if byte
+    /// `i` is lowercase (so "RuSt" gives 0b1010)
+    pub fn property_mask(&self) -> u8 {
+        self.bytes
+            .iter()
+            .enumerate()
+            .filter(|(_, byte)| byte.is_ascii_lowercase())
+            .fold(0, |mask, (i, _)| mask | 1 << i)
+    }
+
+    /// Get whether an editor that modified the image data may copy a chunk of
+    /// this type it doesn't otherwise understand
+    pub fn copy_semantics(&self) -> CopySemantics {
+        if self.is_critical() {
+            CopySemantics::CriticalNotApplicable
+        } else if self.is_safe_to_copy() {
+            CopySemantics::SafeToCopy
+        } else {
+            CopySemantics::MustNotCopyWithModifiedData
+        }
+    }
+
+    /// Get the spec name of a standard chunk type, or None for private and
+    /// unrecognized types
+    pub fn standard_name(&self) -> Option<&'static str> {
+        let name = match &self.bytes() {
+            b"IHDR" => "image header",
+            b"PLTE" => "palette",
+            b"IDAT" => "image data",
+            b"IEND" => "image trailer",
+            b"bKGD" => "background colour",
+            b"cHRM" => "primary chromaticities and white point",
+            b"eXIf" => "exchangeable image file profile",
+            b"gAMA" => "image gamma",
+            b"hIST" => "image histogram",
+            b"iCCP" => "embedded ICC profile",
+            b"iTXt" => "international textual data",
+            b"pHYs" => "physical pixel dimensions",
+            b"sBIT" => "significant bits",
+            b"sPLT" => "suggested palette",
+            b"sRGB" => "standard RGB colour space",
+            b"tEXt" => "textual data",
+            b"tIME" => "image last-modification time",
+            b"tRNS" => "transparency",
+            b"zTXt" => "compressed textual data",
+            _ => return None,
+        };
+        Some(name)
+    }
+
+    /// Construct a ChunkType from a 4 character ASCII string, the same as
+    /// `from_str`
+    pub fn from_ascii_str(value: &str) -> Result<ChunkType> {
+        ChunkType::from_str(value)
+    }
+
+    /// Construct a ChunkType from a string like `from_str`, but also reject
+    /// codes with an invalid reserved bit, which strict decoders refuse
+    pub fn from_str_strict(value: &str) -> Result<ChunkType> {
+        let chunk_type = ChunkType::from_str(value)?;
+        if !chunk_type.is_reserved_bit_valid() {
+            return Err(error_from(
+                "byte 3 must be uppercase per PNG reserved-bit rule",
+            ));
+        }
+        Ok(chunk_type)
+    }
+
+    /// Parse a whitespace separated list of type codes, naming the first
+    /// token that fails to parse
+    pub fn parse_list(s: &str) -> Result<Vec<ChunkType>> {
+        s.split_whitespace()
+            .enumerate()
+            .map(|(i, token)| {
+                ChunkType::from_str(token).map_err(|err| {
+                    error_from(&format!("invalid type code {} ({:?}): {}", i, token, err))
+                })
+            })
+            .collect()
+    }
+
+    /// Get the lowercased chunk type bytes, for use as a case-insensitive key
+    pub fn canonical_lower(&self) -> [u8; 4] {
+        self.bytes().map(|b| b.to_ascii_lowercase())
+    }
+
+    /// Tests whether two chunk types are the same letters, ignoring case
+    pub fn eq_ignore_case(&self, other: &ChunkType) -> bool {
+        self.canonical_lower() == other.canonical_lower()
+    }
+
+    /// Get a copy of this chunk type with the ancillary bit set
+    pub fn to_ancillary(self) -> ChunkType {
+        self.with_property_bit(0, true)
+    }
+
+    /// Get a copy of this chunk type with the ancillary bit cleared
+    pub fn to_critical(self) -> ChunkType {
+        self.with_property_bit(0, false)
+    }
+
+    /// Get a copy of this chunk type with the private bit cleared
+    pub fn to_public(self) -> ChunkType {
+        self.with_property_bit(1, false)
+    }
+
+    /// Get a copy of this chunk type with the private bit set
+    pub fn to_private(self) -> ChunkType {
+        self.with_property_bit(1, true)
+    }
+
+    /// Get a copy of this chunk type with the copy bit set
+    pub fn to_safe_to_copy(self) -> ChunkType {
+        self.with_property_bit(3, true)
+    }
+
+    /// Get a copy of this chunk type with the copy bit cleared
+    pub fn to_unsafe_to_copy(self) -> ChunkType {
+        self.with_property_bit(3, false)
+    }
+
+    /// Set or clear bit 5 of one byte, which only ever changes the case of
+    /// a letter and so always results in a valid chunk type
+    fn with_property_bit(&self, index: usize, set: bool) -> ChunkType {
+        let mut bytes = self.bytes();
+        if set {
+            bytes[index] |= 32u8;
+        } else {
+            bytes[index] &= !32u8;
+        }
+        ChunkType { bytes }
     }
 }
 
@@ -62,12 +209,7 @@ impl TryFrom<[u8; 4]> for ChunkType {
                 _ => return Err(error_from(&format!("byte {} is out of range", i))),
             }
         }
-        Ok(ChunkType {
-            ancillary: value[0],
-            private: value[1],
-            reserved: value[2],
-            safe_to_copy: value[3],
-        })
+        Ok(ChunkType { bytes: value })
     }
 }
 
@@ -76,12 +218,41 @@ impl FromStr for ChunkType {
 
     /// Gives the ability to  construct a ChunkType from a &str
     fn from_str(value: &str) -> Result<Self> {
+        // shells don't always strip quotes, so allow one matching pair
+        let value = ['\'', '"']
+            .iter()
+            .find_map(|&q| value.strip_prefix(q).and_then(|v| v.strip_suffix(q)))
+            .unwrap_or(value);
+        // check this first, as len() counts bytes rather than characters
+        if !value.is_ascii() {
+            return Err(error_from("type code must be ASCII"));
+        }
         if value.len() != 4 {
-            Err(error_from("`value` must be exactly 4 bytes long"))
-        } else {
-            let bytes: [u8; 4] = value.as_bytes().try_into()?;
-            ChunkType::try_from(bytes)
+            return Err(error_from("`value` must be exactly 4 bytes long"));
+        }
+        if value.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(error_from(
+                "PNG chunk type codes are 4 letters (A–Z, a–z), not digits; did you mean a word?",
+            ));
         }
+        // name the offending byte, as stray control bytes from shell quoting
+        // are otherwise invisible
+        if let Some(i) = value.bytes().position(|b| !b.is_ascii_alphabetic()) {
+            return Err(error_from(&format!(
+                "type code contains a control or non-letter byte at position {}: {:#04x}",
+                i,
+                value.as_bytes()[i]
+            )));
+        }
+        let bytes: [u8; 4] = value.as_bytes().try_into()?;
+        ChunkType::try_from(bytes)
+    }
+}
+
+impl Borrow<[u8; 4]> for ChunkType {
+    /// Lets maps keyed by ChunkType be queried with raw type bytes
+    fn borrow(&self) -> &[u8; 4] {
+        &self.bytes
     }
 }
 
@@ -89,7 +260,7 @@ impl Display for ChunkType {
     /// Gives the ability to format ChunkType as its ASCII equivalent
     /// and Enables ToString
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        write!(f, "{}", String::from_utf8_lossy(&self.bytes()))
+        write!(f, "{}", String::from_utf8_lossy(self.as_bytes()))
     }
 }
 
@@ -114,6 +285,77 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    pub fn test_chunk_type_from_quoted_str() {
+        let expected = ChunkType::from_str("RuSt").unwrap();
+        assert_eq!(ChunkType::from_str("'RuSt'").unwrap(), expected);
+        assert_eq!(ChunkType::from_str("\"RuSt\"").unwrap(), expected);
+    }
+
+    #[test]
+    pub fn test_chunk_type_from_badly_quoted_str() {
+        assert!(ChunkType::from_str("'RuSt\"").is_err());
+        assert!(ChunkType::from_str("''RuSt''").is_err());
+        assert!(ChunkType::from_str("'RuS'").is_err());
+        assert!(ChunkType::from_str("'").is_err());
+    }
+
+    #[test]
+    pub fn test_chunk_type_from_non_ascii_str() {
+        // 2 characters, but 4 bytes
+        let err = ChunkType::from_str("é©").unwrap_err();
+        assert_eq!(err.to_string(), "type code must be ASCII");
+        assert!(ChunkType::from_ascii_str("RuSé").is_err());
+    }
+
+    #[test]
+    pub fn test_chunk_type_from_str_with_nul() {
+        let err = ChunkType::from_str("Ru\0t").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "type code contains a control or non-letter byte at position 2: 0x00"
+        );
+        let err = ChunkType::from_str("Ru5t").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "type code contains a control or non-letter byte at position 2: 0x35"
+        );
+    }
+
+    #[test]
+    pub fn test_chunk_type_from_digits() {
+        let err = ChunkType::from_str("1234").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "PNG chunk type codes are 4 letters (A–Z, a–z), not digits; did you mean a word?"
+        );
+        let err = ChunkType::from_str("Ru1t").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "type code contains a control or non-letter byte at position 2: 0x31"
+        );
+    }
+
+    #[test]
+    pub fn test_chunk_type_from_ascii_str() {
+        let expected = ChunkType::try_from(*b"RuSt").unwrap();
+        assert_eq!(ChunkType::from_ascii_str("RuSt").unwrap(), expected);
+    }
+
+    #[test]
+    pub fn test_chunk_type_from_str_strict() {
+        let err = ChunkType::from_str_strict("Rust").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "byte 3 must be uppercase per PNG reserved-bit rule"
+        );
+        assert!(ChunkType::from_str("Rust").is_ok());
+
+        let chunk = ChunkType::from_str_strict("RuSt").unwrap();
+        assert_eq!(&chunk.to_string(), "RuSt");
+        assert!(ChunkType::from_str_strict("Ru1t").is_err());
+    }
+
     #[test]
     pub fn test_chunk_type_is_critical() {
         let chunk = ChunkType::from_str("RuSt").unwrap();
@@ -162,6 +404,21 @@ mod tests {
         assert!(!chunk.is_safe_to_copy());
     }
 
+    #[test]
+    pub fn test_chunk_type_copy_semantics() {
+        let critical = ChunkType::from_str("RuSt").unwrap();
+        assert_eq!(critical.copy_semantics(), CopySemantics::CriticalNotApplicable);
+
+        let safe = ChunkType::from_str("ruSt").unwrap();
+        assert_eq!(safe.copy_semantics(), CopySemantics::SafeToCopy);
+
+        let unsafe_to_copy = ChunkType::from_str("ruST").unwrap();
+        assert_eq!(
+            unsafe_to_copy.copy_semantics(),
+            CopySemantics::MustNotCopyWithModifiedData
+        );
+    }
+
     #[test]
     pub fn test_valid_chunk_is_valid() {
         let chunk = ChunkType::from_str("RuSt").unwrap();
@@ -183,6 +440,104 @@ mod tests {
         assert_eq!(&chunk.to_string(), "RuSt");
     }
 
+    #[test]
+    pub fn test_chunk_type_canonical_lower() {
+        let variants = ["RuSt", "RUST", "rust"].map(|s| ChunkType::from_str(s).unwrap());
+        for chunk in variants.iter() {
+            assert_eq!(&chunk.canonical_lower(), b"rust");
+        }
+        assert!(variants[0].eq_ignore_case(&variants[1]));
+        assert!(!variants[0].eq_ignore_case(&ChunkType::from_str("RuSx").unwrap()));
+    }
+
+    #[test]
+    pub fn test_chunk_type_property_mutators() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+
+        let ancillary = chunk.to_ancillary();
+        assert_eq!(&ancillary.to_string(), "ruSt");
+        assert!(!ancillary.is_critical());
+        assert_eq!(&ancillary.to_critical().to_string(), "RuSt");
+
+        let public = chunk.to_public();
+        assert_eq!(&public.to_string(), "RUSt");
+        assert!(public.is_public());
+        assert_eq!(&public.to_private().to_string(), "RuSt");
+
+        let unsafe_to_copy = chunk.to_unsafe_to_copy();
+        assert_eq!(&unsafe_to_copy.to_string(), "RuST");
+        assert!(!unsafe_to_copy.is_safe_to_copy());
+        assert_eq!(&unsafe_to_copy.to_safe_to_copy().to_string(), "RuSt");
+    }
+
+    #[test]
+    pub fn test_chunk_type_mutators_are_idempotent() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+        assert_eq!(chunk.to_critical(), chunk);
+        assert_eq!(chunk.to_private(), chunk);
+        assert_eq!(chunk.to_safe_to_copy(), chunk);
+    }
+
+    #[test]
+    pub fn test_chunk_type_parse_list() {
+        let types = ChunkType::parse_list(" tEXt zTXt\ttIME ").unwrap();
+        let codes: Vec<String> = types.iter().map(ChunkType::to_string).collect();
+        assert_eq!(codes, ["tEXt", "zTXt", "tIME"]);
+        assert!(ChunkType::parse_list("").unwrap().is_empty());
+    }
+
+    #[test]
+    pub fn test_chunk_type_parse_list_invalid_token() {
+        let err = ChunkType::parse_list("tEXt zT1t tIME").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid type code 1 (\"zT1t\"): type code contains a control or \
+             non-letter byte at position 2: 0x31"
+        );
+    }
+
+    #[test]
+    pub fn test_chunk_type_property_mask() {
+        let mask = |s: &str| ChunkType::from_str(s).unwrap().property_mask();
+        assert_eq!(mask("RuSt"), 0b1010);
+        assert_eq!(mask("IHDR"), 0);
+        assert_eq!(mask("rust"), 0b1111);
+        assert_eq!(mask("tEXt"), 0b1001);
+    }
+
+    #[test]
+    pub fn test_chunk_type_standard_name() {
+        let name = |s: &str| ChunkType::from_str(s).unwrap().standard_name();
+        assert_eq!(name("IHDR"), Some("image header"));
+        assert_eq!(name("IDAT"), Some("image data"));
+        assert_eq!(name("tEXt"), Some("textual data"));
+        assert_eq!(name("pHYs"), Some("physical pixel dimensions"));
+        assert_eq!(name("RuSt"), None);
+        assert_eq!(name("text"), None);
+    }
+
+    #[test]
+    pub fn test_chunk_type_as_bytes() {
+        for code in ["RuSt", "IHDR", "tEXt"] {
+            let chunk_type = ChunkType::from_str(code).unwrap();
+            assert_eq!(chunk_type.as_bytes(), chunk_type.bytes());
+            assert_eq!(chunk_type.as_bytes(), code.as_bytes());
+        }
+    }
+
+    #[test]
+    pub fn test_chunk_type_borrow_as_bytes() {
+        use std::collections::HashMap;
+
+        let mut counts: HashMap<ChunkType, usize> = HashMap::new();
+        counts.insert(ChunkType::from_str("IDAT").unwrap(), 3);
+        counts.insert(ChunkType::from_str("tEXt").unwrap(), 1);
+        assert_eq!(counts.get(b"IDAT"), Some(&3));
+        assert_eq!(counts.get(b"tEXt"), Some(&1));
+        assert_eq!(counts.get(b"TEXT"), None);
+        assert_eq!(counts.get(b"1234"), None);
+    }
+
     #[test]
     pub fn test_chunk_type_trait_impls() {
         let chunk_type_1: ChunkType = TryFrom::try_from([82, 117, 83, 116]).unwrap();
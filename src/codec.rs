@@ -0,0 +1,98 @@
+use crate::{
+    chunk::{check_length, Chunk, LENGTH_WIDTH, REQ_FIELDS_WIDTH},
+    types::{Error, Result},
+};
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Splits a byte stream into PNG chunks, and writes chunks back out
+#[derive(Debug, Default)]
+pub struct ChunkCodec;
+
+impl Decoder for ChunkCodec {
+    type Item = Chunk;
+    type Error = Error;
+
+    /// Gives the ability to read chunks off of a stream, waiting until the
+    /// whole chunk (as indicated by its length field) has arrived. Lengths
+    /// over the spec's limit are rejected rather than waited on
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Chunk>> {
+        if src.len() < LENGTH_WIDTH {
+            return Ok(None);
+        }
+        let chunk_length = u32::from_be_bytes(src[..LENGTH_WIDTH].try_into()?);
+        check_length(u64::from(chunk_length))?;
+        let chunk_length = chunk_length as usize;
+        if src.len() < chunk_length + REQ_FIELDS_WIDTH {
+            return Ok(None);
+        }
+        let chunk_bytes = src.split_to(chunk_length + REQ_FIELDS_WIDTH);
+        Chunk::try_from(&chunk_bytes[..]).map(Some)
+    }
+}
+
+impl Encoder<Chunk> for ChunkCodec {
+    type Error = Error;
+
+    /// Gives the ability to write chunks to a stream
+    fn encode(&mut self, item: Chunk, dst: &mut BytesMut) -> Result<()> {
+        dst.extend_from_slice(&item.as_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_type::ChunkType;
+    use std::str::FromStr;
+
+    fn testing_chunk() -> Chunk {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        Chunk::new(chunk_type, b"This is where your secret message will be!".to_vec())
+    }
+
+    #[test]
+    fn test_decode_fragmented_chunk() {
+        let bytes = testing_chunk().as_bytes();
+        let mut codec = ChunkCodec;
+        let mut buf = BytesMut::new();
+
+        // split in the middle of the length field
+        buf.extend_from_slice(&bytes[..2]);
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        buf.extend_from_slice(&bytes[2..]);
+        let chunk = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(chunk, testing_chunk());
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_decode_waits_for_data() {
+        let bytes = testing_chunk().as_bytes();
+        let mut codec = ChunkCodec;
+        let mut buf = BytesMut::from(&bytes[..20]);
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        assert_eq!(buf.len(), 20);
+    }
+
+    #[test]
+    fn test_decode_rejects_oversized_length() {
+        let mut codec = ChunkCodec;
+        let mut buf = BytesMut::from(&[0xFF, 0xFF, 0xFF, 0xF0, b'R', b'u', b'S', b't'][..]);
+        assert!(codec.decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let mut codec = ChunkCodec;
+        let mut buf = BytesMut::new();
+        codec.encode(testing_chunk(), &mut buf).unwrap();
+        codec.encode(Chunk::new_iend(), &mut buf).unwrap();
+
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), testing_chunk());
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), Chunk::new_iend());
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+    }
+}
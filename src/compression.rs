@@ -0,0 +1,36 @@
+use crate::types::Result;
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use std::io::{Read, Write};
+
+/// Compress data with zlib, as used by PNG (compression method 0)
+pub fn compress(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+/// Decompress zlib data
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>> {
+    let mut decoder = ZlibDecoder::new(data);
+    let mut out: Vec<u8> = Vec::new();
+    decoder.read_to_end(&mut out)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compression_round_trip() {
+        let data = "All work and no play makes Jack a dull boy. ".repeat(20);
+        let compressed = compress(data.as_bytes()).unwrap();
+        assert!(compressed.len() < data.len());
+        assert_eq!(decompress(&compressed).unwrap(), data.as_bytes());
+    }
+
+    #[test]
+    fn test_decompress_invalid() {
+        assert!(decompress(b"this is not zlib data").is_err());
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// How serious a problem found by `Png::diagnose` is
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Severity {
+    /// Allowed by the spec, but suspicious or likely to trip up decoders
+    Warning,
+    /// Breaks the spec, so decoders may refuse the file
+    Error,
+}
+
+/// Which check found a problem, as a stable machine-readable code
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DiagnosticCode {
+    CrcMismatch,
+    InvalidHeader,
+    Ordering,
+    DuplicateSingleton,
+    UnknownCritical,
+    InvalidReservedBit,
+    BadKeyword,
+    IendData,
+    TrailingData,
+}
+
+/// A single problem found by `Png::diagnose`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: DiagnosticCode,
+    /// Index of the chunk the problem is in, if it is about one chunk
+    pub chunk_index: Option<usize>,
+    /// Offset of the problem in `Png::as_bytes`, counting the signature
+    pub offset: Option<usize>,
+    pub message: String,
+}
+
+impl Severity {
+    /// Get the lowercase name of this severity
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+impl DiagnosticCode {
+    /// Get the kebab-case name of this code, as used in output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticCode::CrcMismatch => "crc-mismatch",
+            DiagnosticCode::InvalidHeader => "invalid-header",
+            DiagnosticCode::Ordering => "ordering",
+            DiagnosticCode::DuplicateSingleton => "duplicate-singleton",
+            DiagnosticCode::UnknownCritical => "unknown-critical",
+            DiagnosticCode::InvalidReservedBit => "invalid-reserved-bit",
+            DiagnosticCode::BadKeyword => "bad-keyword",
+            DiagnosticCode::IendData => "iend-data",
+            DiagnosticCode::TrailingData => "trailing-data",
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    /// Gives the ability to format a Diagnostic as
+    /// "severity[code] @offset: message", like a compiler lint
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}[{}]", self.severity.as_str(), self.code.as_str())?;
+        if let Some(offset) = self.offset {
+            write!(f, " @{:#x}", offset)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostic_display() {
+        let mut diagnostic = Diagnostic {
+            severity: Severity::Error,
+            code: DiagnosticCode::CrcMismatch,
+            chunk_index: Some(2),
+            offset: Some(0x21),
+            message: "checksum of gAMA chunk does not match its data".to_owned(),
+        };
+        assert_eq!(
+            diagnostic.to_string(),
+            "error[crc-mismatch] @0x21: checksum of gAMA chunk does not match its data"
+        );
+        diagnostic.severity = Severity::Warning;
+        diagnostic.offset = None;
+        assert!(diagnostic.to_string().starts_with("warning[crc-mismatch]: "));
+        assert!(Severity::Error > Severity::Warning);
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{chunk::Chunk, chunk_type::ChunkType, png::Png};
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+// chunks with at most this much data get the differing byte positions listed
+pub const SMALL_CHUNK_LEN: usize = 256;
+
+/// One difference between two PNGs, see `png_diff`. Indices in `old` refer
+/// to the first PNG and indices in `new` to the second
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ChunkDiff {
+    /// A chunk only in the second PNG
+    Added { chunk_type: ChunkType, new: usize },
+    /// A chunk only in the first PNG
+    Removed { chunk_type: ChunkType, old: usize },
+    /// A chunk in both PNGs, but not in the same order relative to the others
+    Moved {
+        chunk_type: ChunkType,
+        old: usize,
+        new: usize,
+    },
+    /// A chunk in both PNGs with different data. For small chunks of equal
+    /// length, `bytes` lists the data positions that differ
+    Changed {
+        chunk_type: ChunkType,
+        old: usize,
+        new: usize,
+        bytes: Option<Vec<usize>>,
+    },
+}
+
+impl ChunkDiff {
+    /// Get the type of the chunk this difference is about
+    pub fn chunk_type(&self) -> &ChunkType {
+        match self {
+            ChunkDiff::Added { chunk_type, .. }
+            | ChunkDiff::Removed { chunk_type, .. }
+            | ChunkDiff::Moved { chunk_type, .. }
+            | ChunkDiff::Changed { chunk_type, .. } => chunk_type,
+        }
+    }
+}
+
+impl Display for ChunkDiff {
+    /// Gives the ability to format a ChunkDiff as a one line summary
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ChunkDiff::Added { chunk_type, new } => {
+                write!(f, "added {} at index {}", chunk_type, new)
+            }
+            ChunkDiff::Removed { chunk_type, old } => {
+                write!(f, "removed {} at index {}", chunk_type, old)
+            }
+            ChunkDiff::Moved {
+                chunk_type,
+                old,
+                new,
+            } => write!(f, "moved {} from index {} to {}", chunk_type, old, new),
+            ChunkDiff::Changed {
+                chunk_type,
+                new,
+                bytes,
+                ..
+            } => {
+                write!(f, "changed {} at index {}", chunk_type, new)?;
+                if let Some(bytes) = bytes {
+                    let list: Vec<String> = bytes.iter().map(usize::to_string).collect();
+                    write!(f, " (bytes {})", list.join(", "))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Compare two PNGs chunk by chunk. The nth chunk of a type in one PNG is
+/// matched up with the nth chunk of that type in the other; unmatched chunks
+/// are added or removed, and matched ones can be moved and/or changed.
+/// Additions come first in the order of `new`, then the rest
+pub fn png_diff(old: &Png, new: &Png) -> Vec<ChunkDiff> {
+    let pairs = match_chunks(old.chunks(), new.chunks());
+    let mut diffs = Vec::new();
+
+    let mut matched_old = vec![false; old.chunks().len()];
+    let mut matched_new = vec![false; new.chunks().len()];
+    for &(i, j) in &pairs {
+        matched_old[i] = true;
+        matched_new[j] = true;
+    }
+    for (j, chunk) in new.chunks().iter().enumerate() {
+        if !matched_new[j] {
+            diffs.push(ChunkDiff::Added {
+                chunk_type: *chunk.chunk_type(),
+                new: j,
+            });
+        }
+    }
+    for (i, chunk) in old.chunks().iter().enumerate() {
+        if !matched_old[i] {
+            diffs.push(ChunkDiff::Removed {
+                chunk_type: *chunk.chunk_type(),
+                old: i,
+            });
+        }
+    }
+
+    let in_order = longest_increasing(&pairs);
+    for (k, &(i, j)) in pairs.iter().enumerate() {
+        let chunk_type = *old.chunks()[i].chunk_type();
+        if !in_order[k] {
+            diffs.push(ChunkDiff::Moved {
+                chunk_type,
+                old: i,
+                new: j,
+            });
+        }
+        let (old_data, new_data) = (old.chunks()[i].data(), new.chunks()[j].data());
+        if old_data != new_data {
+            let bytes = (old_data.len() == new_data.len() && old_data.len() <= SMALL_CHUNK_LEN)
+                .then(|| (0..old_data.len()).filter(|&b| old_data[b] != new_data[b]).collect());
+            diffs.push(ChunkDiff::Changed {
+                chunk_type,
+                old: i,
+                new: j,
+                bytes,
+            });
+        }
+    }
+    diffs
+}
+
+/// Pair the nth chunk of each type in `old` with the nth of that type in
+/// `new`, returning (old index, new index) pairs in old order
+fn match_chunks(old: &[Chunk], new: &[Chunk]) -> Vec<(usize, usize)> {
+    let mut by_type: HashMap<ChunkType, Vec<usize>> = HashMap::new();
+    for (j, chunk) in new.iter().enumerate().rev() {
+        by_type.entry(*chunk.chunk_type()).or_default().push(j);
+    }
+    old.iter()
+        .enumerate()
+        .filter_map(|(i, chunk)| {
+            let j = by_type.get_mut(chunk.chunk_type())?.pop()?;
+            Some((i, j))
+        })
+        .collect()
+}
+
+/// Mark the pairs in a longest run whose new indices increase, which are the
+/// ones that kept their order; the rest count as moved
+fn longest_increasing(pairs: &[(usize, usize)]) -> Vec<bool> {
+    // lengths[k] is the length of the longest run ending at pair k
+    let mut lengths = vec![1usize; pairs.len()];
+    let mut previous: Vec<Option<usize>> = vec![None; pairs.len()];
+    for k in 0..pairs.len() {
+        for p in 0..k {
+            if pairs[p].1 < pairs[k].1 && lengths[p] + 1 > lengths[k] {
+                lengths[k] = lengths[p] + 1;
+                previous[k] = Some(p);
+            }
+        }
+    }
+
+    let mut in_order = vec![false; pairs.len()];
+    let mut next = (0..pairs.len()).max_by_key(|&k| lengths[k]);
+    while let Some(k) = next {
+        in_order[k] = true;
+        next = previous[k];
+    }
+    in_order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::TextChunk;
+    use std::str::FromStr;
+
+    fn chunk(chunk_type: &str, data: &[u8]) -> Chunk {
+        Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data.to_vec())
+    }
+
+    fn testing_png() -> Png {
+        Png::from_chunks(vec![
+            chunk("IHDR", b"header"),
+            chunk("gAMA", b"gama"),
+            chunk("tIME", b"time"),
+            chunk("IDAT", b"pixels"),
+            chunk("IDAT", b"more pixels"),
+            Chunk::new_iend(),
+        ])
+    }
+
+    #[test]
+    fn test_png_diff_identical() {
+        assert!(png_diff(&testing_png(), &testing_png()).is_empty());
+    }
+
+    #[test]
+    fn test_png_diff() {
+        let old = testing_png();
+        let new = Png::from_chunks(vec![
+            chunk("IHDR", b"header"),
+            chunk("tIME", b"tame"),
+            chunk("IDAT", b"pixels"),
+            chunk("IDAT", b"more pixels!"),
+            chunk("gAMA", b"gama"),
+            TextChunk::new("Title", "Dice").unwrap().to_chunk(),
+            Chunk::new_iend(),
+        ]);
+        let summary: Vec<String> = png_diff(&old, &new).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            summary,
+            [
+                "added tEXt at index 5",
+                "moved gAMA from index 1 to 4",
+                "changed tIME at index 1 (bytes 1)",
+                "changed IDAT at index 3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_png_diff_removed() {
+        let old = testing_png();
+        let mut new = testing_png();
+        new.remove_all_chunks("IDAT");
+        let diffs = png_diff(&old, &new);
+        assert_eq!(
+            diffs,
+            [
+                ChunkDiff::Removed {
+                    chunk_type: ChunkType::from_str("IDAT").unwrap(),
+                    old: 3,
+                },
+                ChunkDiff::Removed {
+                    chunk_type: ChunkType::from_str("IDAT").unwrap(),
+                    old: 4,
+                },
+            ]
+        );
+        assert!(diffs.iter().all(|d| d.chunk_type().is_critical()));
+    }
+}
@@ -0,0 +1,115 @@
+use crate::types::Error;
+use std::{
+    array::TryFromSliceError,
+    error,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+/// Whether an error was caused by bad input or by a bug in this program
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ErrorKind {
+    /// Bad input, such as a corrupt file or an invalid type code
+    User,
+    /// An invariant that should always hold didn't, which is worth reporting
+    Internal,
+}
+
+/// Returned when a chunk's checksum does not match its type and data
+#[derive(Debug)]
+pub struct CrcMismatch;
+
+impl Display for CrcMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "checksum does not match data")
+    }
+}
+
+impl error::Error for CrcMismatch {}
+
+/// Returned when data does not start with the PNG signature
+#[derive(Debug)]
+pub struct InvalidSignature;
+
+impl Display for InvalidSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "invalid png (incorrect header)")
+    }
+}
+
+impl error::Error for InvalidSignature {}
+
+/// Returned when an internal invariant does not hold, with context
+#[derive(Debug)]
+pub struct InternalError(pub String);
+
+impl Display for InternalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl error::Error for InternalError {}
+
+/// Tests whether an error was caused by a checksum mismatch
+pub fn is_crc_mismatch(err: &Error) -> bool {
+    err.is::<CrcMismatch>()
+}
+
+/// Tests whether an error was caused by a missing or incorrect PNG signature
+pub fn is_invalid_signature(err: &Error) -> bool {
+    err.is::<InvalidSignature>()
+}
+
+/// Classify an error as a user or internal error. Slice conversion errors
+/// count as internal, since the length checks before them should rule them out
+pub fn error_kind(err: &Error) -> ErrorKind {
+    if err.is::<InternalError>() || err.is::<TryFromSliceError>() {
+        ErrorKind::Internal
+    } else {
+        ErrorKind::User
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chunk::Chunk, png::Png, types::error_from};
+
+    #[test]
+    fn test_is_crc_mismatch() {
+        let mut bytes = Chunk::new_iend().as_bytes();
+        bytes[11] ^= 1;
+        let err = Chunk::try_from(bytes.as_ref()).unwrap_err();
+        assert!(is_crc_mismatch(&err));
+        assert!(!is_invalid_signature(&err));
+    }
+
+    #[test]
+    fn test_is_invalid_signature() {
+        let err = Png::try_from(&b"GIF89a"[..]).unwrap_err();
+        assert!(is_invalid_signature(&err));
+        assert!(!is_crc_mismatch(&err));
+    }
+
+    #[test]
+    fn test_error_kind() {
+        let mut bytes = Chunk::new_iend().as_bytes();
+        bytes[11] ^= 1;
+        let crc_err = Chunk::try_from(bytes.as_ref()).unwrap_err();
+        assert_eq!(error_kind(&crc_err), ErrorKind::User);
+        assert_eq!(error_kind(&error_from("bad type code")), ErrorKind::User);
+
+        let slice_err: Error = <[u8; 4]>::try_from(&bytes[..2]).unwrap_err().into();
+        assert_eq!(error_kind(&slice_err), ErrorKind::Internal);
+        let internal: Error = InternalError("failed to read CRC field".to_owned()).into();
+        assert_eq!(error_kind(&internal), ErrorKind::Internal);
+        assert_eq!(internal.to_string(), "failed to read CRC field");
+    }
+
+    #[test]
+    fn test_other_errors_match_nothing() {
+        let err = error_from("checksum does not match data");
+        assert!(!is_crc_mismatch(&err));
+        assert!(!is_invalid_signature(&err));
+    }
+}
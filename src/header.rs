@@ -0,0 +1,193 @@
+use crate::{
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    types::{assert_or_err, Error, Result},
+};
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    str::FromStr,
+};
+
+// IHDR data layout
+pub const IHDR_DATA_WIDTH: usize = 13;
+pub const MAX_DIMENSION: u32 = (1 << 31) - 1;
+
+// colour types
+pub const GRAYSCALE: u8 = 0;
+pub const TRUECOLOR: u8 = 2;
+pub const INDEXED: u8 = 3;
+pub const GRAYSCALE_ALPHA: u8 = 4;
+pub const TRUECOLOR_ALPHA: u8 = 6;
+
+/// Stores the contents of a PNG IHDR chunk
+#[derive(Debug, PartialEq, Eq)]
+pub struct ImageHeader {
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: u8,
+    interlace_method: u8,
+}
+
+impl ImageHeader {
+    /// Chunk type code of image header chunks
+    pub const CHUNK_TYPE: &'static str = "IHDR";
+
+    /// Create a new, non-interlaced image header, validating its fields
+    pub fn new(width: u32, height: u32, bit_depth: u8, color_type: u8) -> Result<ImageHeader> {
+        let header = ImageHeader {
+            width,
+            height,
+            bit_depth,
+            color_type,
+            interlace_method: 0,
+        };
+        header.validate()?;
+        Ok(header)
+    }
+
+    /// Get the image width in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the image height in pixels
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the number of bits per sample (or per palette index)
+    pub fn bit_depth(&self) -> u8 {
+        self.bit_depth
+    }
+
+    /// Get the colour type code
+    pub fn color_type(&self) -> u8 {
+        self.color_type
+    }
+
+    /// Get the interlace method (0 for none, 1 for Adam7)
+    pub fn interlace_method(&self) -> u8 {
+        self.interlace_method
+    }
+
+    /// Tests whether the image is stored as palette indices, which means
+    /// it needs a PLTE chunk
+    pub fn is_indexed(&self) -> bool {
+        self.color_type == INDEXED
+    }
+
+    /// Build an IHDR chunk from this header
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = Vec::with_capacity(IHDR_DATA_WIDTH);
+        data.extend(self.width.to_be_bytes());
+        data.extend(self.height.to_be_bytes());
+        // compression and filter methods are always 0
+        data.extend([self.bit_depth, self.color_type, 0, 0, self.interlace_method]);
+        let chunk_type = ChunkType::from_str(ImageHeader::CHUNK_TYPE)
+            .expect("IHDR is a valid chunk type");
+        Chunk::new(chunk_type, data)
+    }
+
+    /// Check the header fields against the spec
+    fn validate(&self) -> Result<()> {
+        assert_or_err(
+            (1..=MAX_DIMENSION).contains(&self.width) && (1..=MAX_DIMENSION).contains(&self.height),
+            "invalid image header (dimensions out of range)",
+        )?;
+        let allowed_depths: &[u8] = match self.color_type {
+            GRAYSCALE => &[1, 2, 4, 8, 16],
+            INDEXED => &[1, 2, 4, 8],
+            TRUECOLOR | GRAYSCALE_ALPHA | TRUECOLOR_ALPHA => &[8, 16],
+            _ => &[],
+        };
+        assert_or_err(
+            !allowed_depths.is_empty(),
+            "invalid image header (unknown colour type)",
+        )?;
+        assert_or_err(
+            allowed_depths.contains(&self.bit_depth),
+            "invalid image header (bit depth not allowed for colour type)",
+        )?;
+        assert_or_err(
+            self.interlace_method <= 1,
+            "invalid image header (unknown interlace method)",
+        )
+    }
+}
+
+impl TryFrom<&Chunk> for ImageHeader {
+    type Error = Error;
+    /// Gives the ability to parse an IHDR chunk
+    fn try_from(chunk: &Chunk) -> Result<Self> {
+        assert_or_err(
+            chunk.chunk_type().to_string() == ImageHeader::CHUNK_TYPE,
+            "chunk is not an IHDR chunk",
+        )?;
+        let data = chunk.data();
+        assert_or_err(
+            data.len() == IHDR_DATA_WIDTH,
+            "invalid image header (incorrect length)",
+        )?;
+        assert_or_err(
+            data[10] == 0 && data[11] == 0,
+            "invalid image header (unknown compression or filter method)",
+        )?;
+        let header = ImageHeader {
+            width: u32::from_be_bytes(data[0..4].try_into()?),
+            height: u32::from_be_bytes(data[4..8].try_into()?),
+            bit_depth: data[8],
+            color_type: data[9],
+            interlace_method: data[12],
+        };
+        header.validate()?;
+        Ok(header)
+    }
+}
+
+impl Display for ImageHeader {
+    /// Gives the ability to format ImageHeader as a string
+    /// and Enables ToString
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "ImageHeader {{Size: {}x{}, Bit depth: {}, Colour type: {}, Interlace: {}}}",
+            self.width, self.height, self.bit_depth, self.color_type, self.interlace_method
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_header_round_trip() {
+        let header = ImageHeader::new(50, 40, 8, TRUECOLOR_ALPHA).unwrap();
+        let chunk = header.to_chunk();
+        assert_eq!(&chunk.chunk_type().to_string(), "IHDR");
+        assert_eq!(chunk.length(), 13);
+        assert_eq!(ImageHeader::try_from(&chunk).unwrap(), header);
+    }
+
+    #[test]
+    fn test_image_header_invalid_fields() {
+        assert!(ImageHeader::new(0, 1, 8, TRUECOLOR).is_err());
+        assert!(ImageHeader::new(1, 1 << 31, 8, TRUECOLOR).is_err());
+        assert!(ImageHeader::new(1, 1, 4, TRUECOLOR).is_err());
+        assert!(ImageHeader::new(1, 1, 16, INDEXED).is_err());
+        assert!(ImageHeader::new(1, 1, 8, 5).is_err());
+    }
+
+    #[test]
+    fn test_image_header_wrong_length() {
+        let chunk = Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![0; 12]);
+        assert!(ImageHeader::try_from(&chunk).is_err());
+    }
+
+    #[test]
+    fn test_image_header_is_indexed() {
+        assert!(ImageHeader::new(1, 1, 8, INDEXED).unwrap().is_indexed());
+        assert!(!ImageHeader::new(1, 1, 8, GRAYSCALE).unwrap().is_indexed());
+    }
+}
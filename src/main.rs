@@ -3,7 +3,18 @@
 mod types;
 mod chunk_type;
 mod chunk;
+#[cfg(feature = "async")]
+mod codec;
+#[cfg(feature = "compression")]
+mod compression;
+mod diagnostic;
+mod diff;
+mod error;
+mod header;
+#[cfg(feature = "memmap2")]
+mod mmap;
 mod png;
+mod text;
 //mod args;
 //mod commands;
 
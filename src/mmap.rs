@@ -0,0 +1,86 @@
+use crate::{
+    chunk::{Chunk, LENGTH_WIDTH, REQ_FIELDS_WIDTH, TYPE_WIDTH},
+    png::{ChunkIndexEntry, Png},
+    types::{error_from, Result},
+};
+use memmap2::Mmap;
+use std::{fs::File, io::Cursor, ops::Range, path::Path};
+
+/// A PNG file mapped into memory with its chunks indexed, where chunk data is
+/// only copied out when asked for; see `Png::from_mmap`
+#[derive(Debug)]
+pub struct MappedPng {
+    map: Mmap,
+    entries: Vec<ChunkIndexEntry>,
+}
+
+impl MappedPng {
+    /// Map a PNG file into memory and index its chunks
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<MappedPng> {
+        let file = File::open(path)?;
+        // the map is only sound as long as nothing else truncates or rewrites
+        // the file while it is open, which can't be checked from here
+        let map = unsafe { Mmap::map(&file)? };
+        let entries = Png::index_file(&mut Cursor::new(&map[..]))?;
+        Ok(MappedPng { map, entries })
+    }
+
+    /// Get where each chunk is in the file, in order
+    pub fn entries(&self) -> &[ChunkIndexEntry] {
+        &self.entries
+    }
+
+    /// Get the data of the chunk at `index` straight out of the map, without
+    /// copying or verifying it
+    pub fn chunk_data(&self, index: usize) -> Option<&[u8]> {
+        let entry = self.entries.get(index)?;
+        let begin = entry.offset as usize + LENGTH_WIDTH + TYPE_WIDTH;
+        self.map.get(begin..begin + entry.length as usize)
+    }
+
+    /// Copy the chunk at `index` out of the map, verifying its checksum
+    pub fn chunk(&self, index: usize) -> Result<Chunk> {
+        let range = self
+            .byte_range(index)
+            .ok_or_else(|| error_from(&format!("no chunk at index {}", index)))?;
+        Chunk::try_from(&self.map[range])
+    }
+
+    /// Copy every chunk out of the map into a Png
+    pub fn to_png(&self) -> Result<Png> {
+        Png::try_from(&self.map[..])
+    }
+
+    /// Get the range of bytes the chunk at `index` takes up in the file
+    fn byte_range(&self, index: usize) -> Option<Range<usize>> {
+        let entry = self.entries.get(index)?;
+        let begin = entry.offset as usize;
+        Some(begin..begin + REQ_FIELDS_WIDTH + entry.length as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_type::ChunkType;
+    use std::{fs, str::FromStr};
+
+    #[test]
+    fn test_mapped_png() {
+        let mut png = Png::default();
+        let data = vec![0x5Au8; 64 * 1024];
+        png.insert_chunk(1, Chunk::new(ChunkType::from_str("RuSt").unwrap(), data.clone()))
+            .unwrap();
+        let name = format!("pngme-mmap-test-{}.png", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        png.save(&path).unwrap();
+
+        let mapped = MappedPng::open(&path).unwrap();
+        assert_eq!(mapped.entries().len(), png.chunks().len());
+        assert_eq!(mapped.chunk_data(1), Some(data.as_slice()));
+        assert_eq!(mapped.chunk(1).unwrap(), png.chunks()[1]);
+        assert!(mapped.chunk(png.chunks().len()).is_err());
+        assert_eq!(mapped.to_png().unwrap(), png);
+        fs::remove_file(&path).unwrap();
+    }
+}
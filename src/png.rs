@@ -1,52 +1,447 @@
+#[cfg(feature = "compression")]
+use crate::compression;
+#[cfg(feature = "memmap2")]
+use crate::mmap::MappedPng;
 use crate::{
-    chunk::{Chunk, LENGTH_WIDTH, REQ_FIELDS_WIDTH},
+    chunk::{
+        Chunk, ChunkRef, CRC_WIDTH, LENGTH_WIDTH, MAX_CHUNK_LENGTH, REQ_FIELDS_WIDTH, TYPE_WIDTH,
+    },
+    chunk_type::ChunkType,
+    diagnostic::{Diagnostic, DiagnosticCode, Severity},
+    error::InvalidSignature,
+    header::{ImageHeader, TRUECOLOR_ALPHA},
+    text::{self, TextChunk},
     types::{assert_or_err, error_from, Error, Result},
 };
 use std::{
+    collections::{HashMap, HashSet},
     convert::TryFrom,
     fmt::{Display, Formatter, Result as FmtResult},
     fs,
-    io::Read,
+    io::{self, Read, Seek, SeekFrom, Write},
+    ops::Range,
     path::Path,
+    slice, vec,
 };
 
+// ancillary chunks the spec requires to come before PLTE, after PLTE or
+// before IDAT (on top of the ones before PLTE, which also come before IDAT)
+const BEFORE_PLTE: [&str; 5] = ["cHRM", "gAMA", "iCCP", "sBIT", "sRGB"];
+const AFTER_PLTE: [&str; 3] = ["bKGD", "hIST", "tRNS"];
+const BEFORE_IDAT: [&str; 3] = ["pHYs", "sPLT", "oFFs"];
+
+// zlib stream of the only scanline of a 1x1 RGBA image: a filter type byte
+// followed by one fully transparent pixel
+const BLANK_PIXEL_IDAT: [u8; 11] = [120, 218, 99, 96, 0, 2, 0, 0, 5, 0, 1];
+
+/// Describes where a chunk is in a file, without its data
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ChunkIndexEntry {
+    /// Offset of the start of the chunk (its length field) in the file
+    pub offset: u64,
+    pub chunk_type: ChunkType,
+    pub length: u32,
+    /// The checksum stored in the file, which is not verified
+    pub crc: u32,
+}
+
+/// Iterator over the chunks of a PNG read from `R`, see `Png::chunk_iter`
+pub struct PngChunks<R: Read> {
+    reader: R,
+    started: bool,
+    done: bool,
+}
+
+/// Describes what an ancillary chunk holds, see `Png::scan_messages`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MessageEntry {
+    pub chunk_type: ChunkType,
+    pub length: u32,
+    /// Whether the data decoded as printable UTF-8 text
+    pub is_text: bool,
+    /// The decoded text, or a note describing the binary data
+    pub text: String,
+}
+
+/// A PNG image borrowed from a buffer of raw bytes, for inspecting a file
+/// without copying any chunk data; `to_owned` gives a `Png` when needed
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PngRef<'a> {
+    bytes: &'a [u8],
+    chunks: Vec<ChunkRef<'a>>,
+    trailer: &'a [u8],
+}
+
+/// Where `Png::insert_chunk_at_position` should put a chunk
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InsertPosition {
+    /// Directly after the IHDR chunk
+    AfterIHDR,
+    /// Directly before the first IDAT chunk
+    BeforeIDAT,
+    /// Directly before the IEND chunk
+    BeforeIEND,
+    /// At a given index, like `Png::insert_chunk`
+    AtIndex(usize),
+}
+
+/// Controls how strictly `Png::parse_with_options` checks each chunk. The
+/// default matches `Png::try_from`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ParseOptions {
+    /// Check each chunk's checksum against its type and data
+    pub verify_crcs: bool,
+    /// Accept critical chunk types that the spec doesn't define
+    pub allow_unknown_critical: bool,
+    /// Accept chunk types with a lowercase third letter (the reserved bit)
+    pub allow_bad_reserved_bit: bool,
+    /// Reject chunks holding more data than this, if set
+    pub max_chunk_size: Option<u32>,
+    /// Keep a copy of the input so that `Png::as_bytes` can return it as is
+    /// until the chunks are changed, at the cost of holding the file twice
+    pub cache_source: bool,
+}
+
 /// Stores a PNG image
 #[derive(Debug)]
 pub struct Png {
     header: [u8; 8],
     chunks: Vec<Chunk>,
+    // anything stored after the last chunk, written back out as is
+    trailer: Vec<u8>,
+    // the bytes this Png was parsed from, if `ParseOptions::cache_source`
+    // asked for them, kept only until the chunks are changed (through
+    // `chunks_mut`) so that `as_bytes` can reuse them
+    source: Option<Vec<u8>>,
+    // where each chunk started in the input it was parsed from, dropped along
+    // with `source` once the chunks are changed
+    chunk_offsets: Option<Vec<usize>>,
 }
 
 impl Png {
     /// Standard PNG header ("magic bytes")
     pub const STANDARD_HEADER: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
 
+    /// How far into the input `try_from_lenient_start` looks for the header
+    pub const LENIENT_START_WINDOW: usize = 1024;
+
+    /// Chunk types the spec allows at most one of
+    pub const SINGLETON_TYPES: [&'static str; 14] = [
+        "IHDR", "PLTE", "IEND", "cHRM", "gAMA", "iCCP", "sBIT", "sRGB", "bKGD", "hIST", "tRNS",
+        "pHYs", "tIME", "eXIf",
+    ];
+
     /// Create a PNG from a vector of chunks
     pub fn from_chunks(chunks: Vec<Chunk>) -> Self {
         Png {
             header: Png::STANDARD_HEADER,
             chunks,
+            trailer: Vec::new(),
+            source: None,
+            chunk_offsets: None,
         }
     }
 
+    /// Create a PNG from its chunks and any bytes that follow them
+    pub fn from_parts(chunks: Vec<Chunk>, trailer: Vec<u8>) -> Self {
+        let mut png = Png::from_chunks(chunks);
+        png.trailer = trailer;
+        png
+    }
+
+    /// Split this PNG into its chunks and any bytes that follow them
+    pub fn into_parts(self) -> (Vec<Chunk>, Vec<u8>) {
+        (self.chunks, self.trailer)
+    }
+
+    /// Create an empty PNG with room for `capacity` chunks
+    pub fn with_capacity(capacity: usize) -> Self {
+        Png::from_chunks(Vec::with_capacity(capacity))
+    }
+
+    /// Make room for at least `additional` more chunks
+    pub fn reserve(&mut self, additional: usize) {
+        self.chunks.reserve(additional);
+    }
+
+    /// Get the number of chunks this PNG can hold without reallocating
+    pub fn capacity(&self) -> usize {
+        self.chunks.capacity()
+    }
+
     /// Load a PNG image from a file
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let mut file = fs::File::open(path)?;
-        let mut buff: Vec<u8> = Vec::new();
-        file.read_to_end(&mut buff);
+        let buff = fs::read(path)?;
+        check_file_signature(&buff)?;
         Png::try_from(buff.as_slice())
     }
 
+    /// Save this PNG image to a file, replacing any existing file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+        self.write_to(&mut file)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Map a PNG file into memory and index its chunks, copying chunk data
+    /// out only when it is asked for. Suited to files too big to load whole
+    #[cfg(feature = "memmap2")]
+    pub fn from_mmap<P: AsRef<Path>>(path: P) -> Result<MappedPng> {
+        MappedPng::open(path)
+    }
+
+    /// Parse a PNG from a reader a chunk at a time, rather than needing all
+    /// of its bytes up front. Anything after IEND is kept as the trailer
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self> {
+        read_signature(&mut reader)?;
+        let mut chunks: Vec<Chunk> = Vec::new();
+        let mut offsets = Vec::new();
+        let mut offset = Png::STANDARD_HEADER.len();
+        while let Some(chunk) = Chunk::read_from(&mut reader)? {
+            let is_iend = chunk.chunk_type().as_bytes() == b"IEND";
+            offsets.push(offset);
+            offset += chunk.total_len();
+            chunks.push(chunk);
+            if is_iend {
+                break;
+            }
+        }
+        assert_or_err(!chunks.is_empty(), "invalid png (incomplete chunk)")?;
+        let mut trailer: Vec<u8> = Vec::new();
+        reader.read_to_end(&mut trailer)?;
+        let mut png = Png::from_parts(chunks, trailer);
+        png.chunk_offsets = Some(offsets);
+        Ok(png)
+    }
+
+    /// Parse a PNG from an async reader a chunk at a time, without blocking
+    #[cfg(feature = "async")]
+    pub async fn from_async_reader<R>(mut reader: R) -> Result<Self>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        use tokio::io::AsyncReadExt;
+
+        let mut header = [0u8; 8];
+        if reader.read_exact(&mut header).await.is_err() || header != Png::STANDARD_HEADER {
+            return Err(InvalidSignature.into());
+        }
+        let mut chunks: Vec<Chunk> = Vec::new();
+        let mut offsets = Vec::new();
+        let mut offset = Png::STANDARD_HEADER.len();
+        while let Some(chunk) = Chunk::read_from_async(&mut reader).await? {
+            let is_iend = chunk.chunk_type().as_bytes() == b"IEND";
+            offsets.push(offset);
+            offset += chunk.total_len();
+            chunks.push(chunk);
+            if is_iend {
+                break;
+            }
+        }
+        assert_or_err(!chunks.is_empty(), "invalid png (incomplete chunk)")?;
+        let mut trailer: Vec<u8> = Vec::new();
+        reader.read_to_end(&mut trailer).await?;
+        let mut png = Png::from_parts(chunks, trailer);
+        png.chunk_offsets = Some(offsets);
+        Ok(png)
+    }
+
+    /// Parse raw PNG bytes, checking each chunk as strictly as `options` asks.
+    /// Anything after IEND is kept as the trailer
+    pub fn parse_with_options(bytes: &[u8], options: &ParseOptions) -> Result<Png> {
+        let mut chunks: Vec<Chunk> = Vec::new();
+        let mut offsets = Vec::new();
+        let mut offset = Png::STANDARD_HEADER.len();
+        let trailer = for_each_chunk_slice(bytes, |chunk_bytes| {
+            chunks.push(options.parse_chunk(chunk_bytes, chunks.len())?);
+            offsets.push(offset);
+            offset += chunk_bytes.len();
+            Ok(())
+        })?;
+        let mut png = Png::from_parts(chunks, trailer.to_vec());
+        png.chunk_offsets = Some(offsets);
+        // unverified input may hold bad checksums, which `as_bytes` must not
+        // hand back, so only cache it if every checksum turned out fine
+        let crcs_ok = options.verify_crcs
+            || png.chunks.iter().all(|chunk| chunk.stored_crc() == Some(chunk.crc()));
+        if options.cache_source && crcs_ok {
+            png.source = Some(bytes.to_vec());
+        }
+        Ok(png)
+    }
+
+    /// Parse raw PNG bytes into a caller-provided chunk vector, clearing it
+    /// first, so that its allocation can be reused across many parses. Any
+    /// trailer after IEND is ignored
+    pub fn parse_into(bytes: &[u8], reuse: &mut Vec<Chunk>) -> Result<()> {
+        reuse.clear();
+        for_each_chunk_slice(bytes, |chunk_bytes| {
+            reuse.push(Chunk::try_from(chunk_bytes)?);
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    /// Parse raw PNG bytes that may have junk before the header, searching
+    /// the first `LENIENT_START_WINDOW` bytes for it. Returns the Png along
+    /// with the offset the header was found at; `chunk_offsets` count from
+    /// the start of `bytes`, junk included
+    pub fn try_from_lenient_start(bytes: &[u8]) -> Result<(Png, usize)> {
+        let search_len = Png::LENIENT_START_WINDOW + Png::STANDARD_HEADER.len();
+        let offset = bytes[..bytes.len().min(search_len)]
+            .windows(Png::STANDARD_HEADER.len())
+            .position(|w| w == Png::STANDARD_HEADER)
+            .ok_or(InvalidSignature)?;
+        let mut png = Png::try_from(&bytes[offset..])?;
+        if let Some(offsets) = &mut png.chunk_offsets {
+            offsets.iter_mut().for_each(|start| *start += offset);
+        }
+        Ok((png, offset))
+    }
+
+    /// Parse as much of a damaged PNG as possible, skipping over anything
+    /// that doesn't form a valid chunk (such as a chunk with a bad checksum or
+    /// length) until the next one that does. Returns what was recovered along
+    /// with a description of each problem, rather than stopping at the first
+    pub fn from_bytes_lossy(bytes: &[u8]) -> (Png, Vec<String>) {
+        let mut log = Vec::new();
+        let mut offset = if bytes.starts_with(&Png::STANDARD_HEADER) {
+            Png::STANDARD_HEADER.len()
+        } else {
+            log.push("missing PNG signature".to_owned());
+            0
+        };
+
+        let mut chunks: Vec<Chunk> = Vec::new();
+        let mut offsets = Vec::new();
+        while offset < bytes.len() {
+            match chunk_ref_at(bytes, offset) {
+                Ok(chunk) => {
+                    offsets.push(offset);
+                    offset += REQ_FIELDS_WIDTH + chunk.data().len();
+                    chunks.push(chunk.to_owned());
+                    if chunk.chunk_type().as_bytes() == b"IEND" {
+                        break;
+                    }
+                }
+                Err(err) => {
+                    let next = (offset + 1..bytes.len())
+                        .find(|&i| chunk_ref_at(bytes, i).is_ok())
+                        .unwrap_or(bytes.len());
+                    log.push(format!(
+                        "skipped {} byte(s) at offset {}: {}",
+                        next - offset,
+                        offset,
+                        err
+                    ));
+                    offset = next;
+                }
+            }
+        }
+
+        let trailer = bytes[offset..].to_vec();
+        if !trailer.is_empty() {
+            log.push(format!("kept {} byte(s) after IEND as a trailer", trailer.len()));
+        }
+        let mut png = Png::from_parts(chunks, trailer);
+        png.chunk_offsets = Some(offsets);
+        (png, log)
+    }
+
+    /// List the chunks of a PNG file (offset, type, length and stored
+    /// checksum) without reading their data, seeking past each data portion.
+    /// Stops after IEND
+    pub fn index_file<R: Read + Seek>(reader: &mut R) -> Result<Vec<ChunkIndexEntry>> {
+        let start = reader.stream_position()?;
+        let end = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(start))?;
+
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header).map_err(|_| InvalidSignature)?;
+        if header != Png::STANDARD_HEADER {
+            return Err(InvalidSignature.into());
+        }
+
+        let mut entries: Vec<ChunkIndexEntry> = Vec::new();
+        let mut offset = reader.stream_position()?;
+        while offset < end {
+            let mut fields = [0u8; LENGTH_WIDTH + TYPE_WIDTH];
+            reader.read_exact(&mut fields)?;
+            let length = u32::from_be_bytes(fields[..LENGTH_WIDTH].try_into()?);
+            let chunk_type = ChunkType::try_from(<[u8; 4]>::try_from(&fields[LENGTH_WIDTH..])?)?;
+            assert_or_err(
+                offset + (REQ_FIELDS_WIDTH as u64) + u64::from(length) <= end,
+                "invalid png (incomplete chunk)",
+            )?;
+            reader.seek(SeekFrom::Current(i64::from(length)))?;
+            let mut crc = [0u8; CRC_WIDTH];
+            reader.read_exact(&mut crc)?;
+            entries.push(ChunkIndexEntry {
+                offset,
+                chunk_type,
+                length,
+                crc: u32::from_be_bytes(crc),
+            });
+            if chunk_type.as_bytes() == b"IEND" {
+                break;
+            }
+            offset = reader.stream_position()?;
+        }
+        Ok(entries)
+    }
+
+    /// Stream the chunks of a PNG one at a time, calling `f` on each without
+    /// keeping them around; stops after IEND, or at the first error from
+    /// parsing or from `f`
+    pub fn for_each_chunk<R: Read>(
+        reader: &mut R,
+        mut f: impl FnMut(&Chunk) -> Result<()>,
+    ) -> Result<()> {
+        read_signature(reader)?;
+        while let Some(chunk) = Chunk::read_from(reader)? {
+            f(&chunk)?;
+            if chunk.chunk_type().as_bytes() == b"IEND" {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Lazily parse the chunks of a PNG from a reader, stopping after IEND
+    pub fn chunk_iter<R: Read>(reader: R) -> PngChunks<R> {
+        PngChunks {
+            reader,
+            started: false,
+            done: false,
+        }
+    }
+
+    /// Add a chunk to this PNG, unless it is a singleton type (see
+    /// `SINGLETON_TYPES`) that the PNG already has a chunk of
+    pub fn append_chunk_unique(&mut self, chunk: Chunk) -> Result<()> {
+        self.check_not_duplicate(&chunk)?;
+        self.append_chunk(chunk);
+        Ok(())
+    }
+
+    /// Insert a chunk at `index` like `insert_chunk`, unless it is a singleton
+    /// type that the PNG already has a chunk of
+    pub fn insert_chunk_unique(&mut self, index: usize, chunk: Chunk) -> Result<()> {
+        self.check_not_duplicate(&chunk)?;
+        self.insert_chunk(index, chunk)
+    }
+
     /// Add a chunk to this PNG
     pub fn append_chunk(&mut self, chunk: Chunk) {
-        self.chunks.push(chunk);
+        self.chunks_mut().push(chunk);
     }
 
     /// Remove and return the first chunk of a given type
     pub fn remove_chunk(&mut self, chunk_type: &str) -> Result<Chunk> {
         for (i, chunk) in self.chunks.iter().enumerate() {
             if chunk.chunk_type().to_string() == chunk_type {
-                return Ok(self.chunks.remove(i));
+                return Ok(self.chunks_mut().remove(i));
             }
         }
         Err(error_from(&format!(
@@ -55,6 +450,87 @@ impl Png {
         )))
     }
 
+    /// Insert a chunk at `index`, shifting later chunks along
+    pub fn insert_chunk(&mut self, index: usize, chunk: Chunk) -> Result<()> {
+        assert_or_err(
+            index <= self.chunks.len(),
+            &format!("cannot insert at index {} of {} chunks", index, self.chunks.len()),
+        )?;
+        self.chunks_mut().insert(index, chunk);
+        Ok(())
+    }
+
+    /// Insert a chunk relative to the critical chunks, erroring if the chunk
+    /// it should go next to is missing. Ancillary chunks placed after IHDR or
+    /// before IDAT or IEND keep the image valid
+    pub fn insert_chunk_at_position(
+        &mut self,
+        position: InsertPosition,
+        chunk: Chunk,
+    ) -> Result<()> {
+        let missing = |anchor: &str| error_from(&format!("no {} chunk to insert next to", anchor));
+        let index = match position {
+            InsertPosition::AfterIHDR => self
+                .indices_of_type("IHDR")
+                .first()
+                .map(|i| i + 1)
+                .ok_or_else(|| missing("IHDR"))?,
+            InsertPosition::BeforeIDAT => self
+                .indices_of_type("IDAT")
+                .first()
+                .copied()
+                .ok_or_else(|| missing("IDAT"))?,
+            InsertPosition::BeforeIEND => self.iend_index().ok_or_else(|| missing("IEND"))?,
+            InsertPosition::AtIndex(index) => index,
+        };
+        self.insert_chunk(index, chunk)
+    }
+
+    /// Remove and return the first chunk of a given type, the same as
+    /// `remove_chunk`
+    pub fn remove_first_chunk(&mut self, chunk_type: &str) -> Result<Chunk> {
+        self.remove_chunk(chunk_type)
+    }
+
+    /// Remove and return every chunk of a given type, in order
+    pub fn remove_all_chunks(&mut self, chunk_type: &str) -> Vec<Chunk> {
+        let (removed, kept) = std::mem::take(self.chunks_mut())
+            .into_iter()
+            .partition(|chunk| chunk.chunk_type().to_string() == chunk_type);
+        self.chunks = kept;
+        removed
+    }
+
+    /// Remove the first chunk whose type matches `chunk_type` ignoring case,
+    /// e.g. "RUST" removes a "ruSt" chunk
+    pub fn remove_chunk_ignore_case(&mut self, chunk_type: &str) -> Result<Chunk> {
+        let query: ChunkType = chunk_type.parse()?;
+        let i = self
+            .chunks
+            .iter()
+            .position(|c| c.chunk_type().eq_ignore_case(&query))
+            .ok_or_else(|| {
+                error_from(&format!(
+                    "chunk with type '{}' (ignoring case) not found",
+                    chunk_type
+                ))
+            })?;
+        Ok(self.chunks_mut().remove(i))
+    }
+
+    /// Get the bytes stored after IEND, usually empty. Extra data here is a
+    /// common sign of an appended archive or a polyglot file
+    pub fn trailer(&self) -> &[u8] {
+        &self.trailer
+    }
+
+    /// Remove and return the bytes stored after IEND, so that they aren't
+    /// written back out
+    pub fn strip_trailer(&mut self) -> Vec<u8> {
+        self.source = None;
+        std::mem::take(&mut self.trailer)
+    }
+
     /// Get the header of this Png
     pub fn header(&self) -> &[u8; 8] {
         &self.header
@@ -65,6 +541,34 @@ impl Png {
         &self.chunks
     }
 
+    /// Iterate over the chunks of this Png
+    pub fn iter(&self) -> slice::Iter<'_, Chunk> {
+        self.chunks.iter()
+    }
+
+    /// Iterate mutably over the chunks of this Png
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, Chunk> {
+        self.chunks_mut().iter_mut()
+    }
+
+    /// Parse the IHDR chunk, which must be the first chunk
+    pub fn ihdr(&self) -> Result<ImageHeader> {
+        let first = self
+            .chunks
+            .first()
+            .ok_or_else(|| error_from("png has no chunks"))?;
+        assert_or_err(
+            first.chunk_type().to_string() == ImageHeader::CHUNK_TYPE,
+            "first chunk is not IHDR",
+        )?;
+        ImageHeader::try_from(first)
+    }
+
+    /// Get the IEND chunk, if there is one
+    pub fn iend(&self) -> Option<&Chunk> {
+        self.iend_index().map(|i| &self.chunks[i])
+    }
+
     /// Search for the first chunk of a given type
     pub fn chunk_by_type(&self, chunk_type: &str) -> Option<&Chunk> {
         for (i, chunk) in self.chunks.iter().enumerate() {
@@ -75,204 +579,3060 @@ impl Png {
         None
     }
 
-    /// Get this Png as a vector of raw bytes
-    pub fn as_bytes(&self) -> Vec<u8> {
-        self.header
+    /// Search for the first chunk whose type matches `chunk_type` ignoring
+    /// case, for users who don't remember the exact casing of a type
+    pub fn chunk_by_type_ignore_case(&self, chunk_type: &str) -> Option<&Chunk> {
+        let query: ChunkType = chunk_type.parse().ok()?;
+        self.chunks
             .iter()
-            .copied()
-            .chain(self.chunks.iter().flat_map(|c| c.as_bytes()))
+            .find(|c| c.chunk_type().eq_ignore_case(&query))
+    }
+
+    /// Iterate over every chunk of a given type, in order
+    pub fn chunks_by_type<'a>(&'a self, chunk_type: &'a str) -> impl Iterator<Item = &'a Chunk> {
+        self.chunks
+            .iter()
+            .filter(move |chunk| chunk.chunk_type().to_string() == chunk_type)
+    }
+
+    /// Get the indices of every chunk of a given type
+    pub fn indices_of_type(&self, chunk_type: &str) -> Vec<usize> {
+        self.chunks
+            .iter()
+            .enumerate()
+            .filter(|(_, chunk)| chunk.chunk_type().to_string() == chunk_type)
+            .map(|(i, _)| i)
             .collect()
     }
-}
 
-impl TryFrom<&[u8]> for Png {
-    type Error = Error;
-    /// Gives the ability to construct a Png from raw bytes
-    fn try_from(bytes: &[u8]) -> Result<Png> {
-        // read the header, erroring if it is not present (file too short)
-        // or incorrect
-        assert_or_err(
-            bytes.len() >= Png::STANDARD_HEADER.len(),
-            "invalid png (incorrect header)",
-        )?;
-        let (header, data) = bytes.split_at(Png::STANDARD_HEADER.len());
-        let header: [u8; 8] = header.try_into()?;
-        assert_or_err(
-            header == Png::STANDARD_HEADER,
-            "invalid png (incorrect header)",
-        )?;
+    /// Iterate over the byte offset of each chunk in `as_bytes`, counting
+    /// from the start of the signature. See `chunk_offsets` for where the
+    /// chunks were in the input this Png was parsed from
+    pub fn offsets(&self) -> impl Iterator<Item = usize> + '_ {
+        self.chunks.iter().scan(self.header.len(), |offset, chunk| {
+            let start = *offset;
+            *offset += REQ_FIELDS_WIDTH + chunk.data().len();
+            Some(start)
+        })
+    }
 
-        // parse the remainder of the file as PNG chunks
-        let mut chunks: Vec<Chunk> = Vec::new();
-        let mut data = data;
-        loop {
-            // read the length, erroring if it is not present
-            assert_or_err(data.len() >= LENGTH_WIDTH, "invalid png (incomplete chunk)")?;
-            let chunk_length = u32::from_be_bytes(data[..LENGTH_WIDTH].try_into()?) as usize;
+    /// Get the byte offset each chunk started at in the input this Png was
+    /// parsed from, which differs from `offsets` when junk was skipped. None
+    /// if the Png wasn't parsed, or once its chunks have been changed
+    pub fn chunk_offsets(&self) -> Option<&[usize]> {
+        self.chunk_offsets.as_deref()
+    }
 
-            // read the rest of the chunk, erroring if it is incomplete
-            assert_or_err(
-                data.len() >= chunk_length + REQ_FIELDS_WIDTH,
-                "invalid png (incomplete chunk)",
-            )?;
-            let chunk_bytes;
-            // keep splitting the chunks off of data...
-            (chunk_bytes, data) = data.split_at(chunk_length + REQ_FIELDS_WIDTH);
-            chunks.push(Chunk::try_from(chunk_bytes)?);
-            // ...until the data is empty
-            if data.is_empty() {
-                break;
-            }
-        }
-        Ok(Png { header, chunks })
+    /// Get the range of bytes the chunk at `index` covers in `as_bytes`
+    /// (counting the header), for reading just that chunk from a file
+    pub fn byte_range(&self, index: usize) -> Option<Range<usize>> {
+        let chunk = self.chunks.get(index)?;
+        let start = self.offsets().nth(index)?;
+        Some(start..start + chunk.total_len())
     }
-}
 
-impl Display for Png {
-    /// Gives the ability to format Png as a string
-    /// and Enables ToString
-    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        writeln!(f, "Png {{",)?;
-        writeln!(f, "  Header: {:?}", self.header())?;
-        writeln!(f, "  Chunks: [")?;
+    /// Find the chunk containing a byte offset in `as_bytes`, returning its
+    /// index too, or None if the offset is in the signature or past the end
+    pub fn chunk_at_offset(&self, offset: usize) -> Option<(usize, &Chunk)> {
+        self.offsets()
+            .zip(self.chunks.iter())
+            .enumerate()
+            .find(|(_, (start, chunk))| {
+                (*start..start + REQ_FIELDS_WIDTH + chunk.data().len()).contains(&offset)
+            })
+            .map(|(i, (_, chunk))| (i, chunk))
+    }
+
+    /// Count the bytes taken up by ancillary chunks, i.e. how much smaller
+    /// the file would be without them
+    pub fn ancillary_byte_count(&self) -> usize {
+        self.chunks
+            .iter()
+            .filter(|chunk| !chunk.is_critical())
+            .map(|chunk| REQ_FIELDS_WIDTH + chunk.data().len())
+            .sum()
+    }
+
+    /// Map each chunk type to the indices of all chunks of that type
+    pub fn index_by_type(&self) -> HashMap<ChunkType, Vec<usize>> {
+        let mut index: HashMap<ChunkType, Vec<usize>> = HashMap::new();
         for (i, chunk) in self.chunks.iter().enumerate() {
-            writeln!(f, "    {}: {}", i, chunk)?;
+            index.entry(*chunk.chunk_type()).or_default().push(i);
         }
-        writeln!(f, "  ]")?;
-        write!(f, "}}",)?;
-        Ok(())
+        index
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::chunk::Chunk;
-    use crate::chunk_type::ChunkType;
-    use std::convert::TryFrom;
-    use std::str::FromStr;
 
-    fn testing_chunks() -> Vec<Chunk> {
-        let mut chunks = Vec::new();
+    /// Check that this Png is structurally valid
+    pub fn validate(&self) -> Result<()> {
+        self.validate_single_ihdr_and_iend()?;
+        self.ensure_iend()?;
+        self.validate_chunk_ordering()
+    }
 
-        chunks.push(chunk_from_strings("FrSt", "I am the first chunk").unwrap());
-        chunks.push(chunk_from_strings("miDl", "I am another chunk").unwrap());
-        chunks.push(chunk_from_strings("LASt", "I am the last chunk").unwrap());
+    /// Check that the common ancillary chunks that must come before PLTE,
+    /// after PLTE or before IDAT do, erroring on the first one that doesn't
+    pub fn validate_chunk_ordering(&self) -> Result<()> {
+        match self.ancillary_ordering_violations().into_iter().next() {
+            Some((_, violation)) => Err(error_from(&violation)),
+            None => Ok(()),
+        }
+    }
 
-        chunks
+    /// List each singleton chunk type (see `SINGLETON_TYPES`) that appears
+    /// more than once
+    pub fn check_singletons(&self) -> Vec<String> {
+        Png::SINGLETON_TYPES
+            .iter()
+            .filter(|t| self.chunks_by_type(t).nth(1).is_some())
+            .map(|t| format!("multiple {} chunks found", t))
+            .collect()
     }
 
-    fn testing_png() -> Png {
-        let chunks = testing_chunks();
-        Png::from_chunks(chunks)
+    /// List every way the chunks break the spec's ordering rules: IHDR must be
+    /// first and IEND last, PLTE must come before IDAT, the IDAT chunks must
+    /// be consecutive, and some ancillary chunks must come before or after
+    /// PLTE or IDAT
+    pub fn check_ordering(&self) -> Vec<String> {
+        self.ordering_violations()
+            .into_iter()
+            .map(|(_, violation)| violation)
+            .collect()
     }
 
-    fn chunk_from_strings(chunk_type: &str, data: &str) -> Result<Chunk> {
-        use std::str::FromStr;
+    /// Check this Png for every problem the other checks know about (bad
+    /// checksums, ordering, duplicate singletons, bad type codes and
+    /// keywords, and so on), listing them all rather than stopping at the
+    /// first like `validate`
+    pub fn diagnose(&self) -> Vec<Diagnostic> {
+        let offsets: Vec<usize> = self.offsets().collect();
+        let mut report = Vec::new();
+        let mut add = |severity, code, chunk_index: Option<usize>, message: String| {
+            report.push(Diagnostic {
+                severity,
+                code,
+                chunk_index,
+                offset: chunk_index.map(|i| offsets[i]),
+                message,
+            });
+        };
 
-        let chunk_type = ChunkType::from_str(chunk_type)?;
-        let data: Vec<u8> = data.bytes().collect();
+        if let Err(err) = self.ihdr() {
+            add(Severity::Error, DiagnosticCode::InvalidHeader, None, err.to_string());
+        }
+        for (i, chunk) in self.chunks.iter().enumerate() {
+            let chunk_type = chunk.chunk_type();
+            if chunk.stored_crc().is_some_and(|crc| crc != chunk.crc()) {
+                add(
+                    Severity::Error,
+                    DiagnosticCode::CrcMismatch,
+                    Some(i),
+                    format!("checksum of {} chunk does not match its data", chunk_type),
+                );
+            }
+            if chunk_type.is_critical() && chunk_type.standard_name().is_none() {
+                add(
+                    Severity::Error,
+                    DiagnosticCode::UnknownCritical,
+                    Some(i),
+                    format!("{} is not a known critical chunk type", chunk_type),
+                );
+            }
+            if !chunk_type.is_reserved_bit_valid() {
+                add(
+                    Severity::Error,
+                    DiagnosticCode::InvalidReservedBit,
+                    Some(i),
+                    format!("{} chunk has an invalid reserved bit", chunk_type),
+                );
+            }
+            if matches!(chunk_type.as_bytes(), b"tEXt" | b"zTXt" | b"iTXt") {
+                let keyword = text::split_keyword(chunk.data())
+                    .and_then(|(keyword, _)| text::encode_keyword(&keyword));
+                if let Err(err) = keyword {
+                    add(
+                        Severity::Error,
+                        DiagnosticCode::BadKeyword,
+                        Some(i),
+                        format!("{} chunk: {}", chunk_type, err),
+                    );
+                }
+            }
+            if chunk_type.as_bytes() == b"IEND" && chunk.length() > 0 {
+                add(
+                    Severity::Warning,
+                    DiagnosticCode::IendData,
+                    Some(i),
+                    format!("IEND chunk holds {} bytes of data", chunk.length()),
+                );
+            }
+        }
 
-        Ok(Chunk::new(chunk_type, data))
+        for (i, violation) in self.ordering_violations() {
+            add(Severity::Error, DiagnosticCode::Ordering, i, violation);
+        }
+        for chunk_type in Png::SINGLETON_TYPES {
+            for &i in self.indices_of_type(chunk_type).iter().skip(1) {
+                add(
+                    Severity::Error,
+                    DiagnosticCode::DuplicateSingleton,
+                    Some(i),
+                    format!("duplicate {} chunk", chunk_type),
+                );
+            }
+        }
+
+        if !self.trailer.is_empty() {
+            report.push(Diagnostic {
+                severity: Severity::Warning,
+                code: DiagnosticCode::TrailingData,
+                chunk_index: None,
+                offset: Some(self.total_len() - self.trailer.len()),
+                message: format!("{} bytes of data after IEND", self.trailer.len()),
+            });
+        }
+        report
     }
 
-    #[test]
-    fn test_from_chunks() {
-        let chunks = testing_chunks();
-        let png = Png::from_chunks(chunks);
+    /// Check that there is exactly one IHDR chunk, at the start, and exactly
+    /// one IEND chunk, at the end, and that PLTE appears at most once,
+    /// erroring with the specific violation
+    pub fn validate_single_ihdr_and_iend(&self) -> Result<()> {
+        for chunk_type in ["IHDR", "PLTE", "IEND"] {
+            assert_or_err(
+                self.indices_of_type(chunk_type).len() <= 1,
+                &format!("multiple {} chunks found", chunk_type),
+            )?;
+        }
+        let ihdr = self
+            .indices_of_type("IHDR")
+            .first()
+            .copied()
+            .ok_or_else(|| error_from("invalid png (missing IHDR)"))?;
+        assert_or_err(ihdr == 0, "IHDR chunk must be the first chunk")?;
+        let iend = self.iend_index().ok_or_else(|| error_from("no IEND chunk"))?;
+        assert_or_err(
+            iend + 1 == self.chunks.len(),
+            "IEND chunk must be the last chunk",
+        )
+    }
 
-        assert_eq!(png.chunks().len(), 3);
+    /// Check that there is an IEND chunk and that it carries no data
+    pub fn ensure_iend(&self) -> Result<()> {
+        let iend = self.iend().ok_or_else(|| error_from("no IEND chunk"))?;
+        assert_or_err(iend.length() == 0, "IEND chunk must not contain data")
     }
 
-    #[test]
-    fn test_valid_from_bytes() {
-        let chunk_bytes: Vec<u8> = testing_chunks()
-            .into_iter()
-            .flat_map(|chunk| chunk.as_bytes())
-            .collect();
+    /// Compare two Pngs, requiring critical chunks to be in the same order but
+    /// allowing ancillary chunks to appear in any order
+    pub fn metadata_eq(&self, other: &Png) -> bool {
+        let is_critical = |c: &&Chunk| c.chunk_type().is_critical();
+        let same_critical = self
+            .chunks
+            .iter()
+            .filter(is_critical)
+            .eq(other.chunks.iter().filter(is_critical));
+        if self.header != other.header || !same_critical {
+            return false;
+        }
 
-        let bytes: Vec<u8> = Png::STANDARD_HEADER
+        // match up each ancillary chunk with an unused equal one in `other`
+        let mut unmatched: Vec<&Chunk> = other
+            .chunks
             .iter()
-            .chain(chunk_bytes.iter())
-            .copied()
+            .filter(|c| !is_critical(c))
             .collect();
-
-        let png = Png::try_from(bytes.as_ref());
-
-        assert!(png.is_ok());
+        for chunk in self.chunks.iter().filter(|c| !is_critical(c)) {
+            match unmatched.iter().position(|&c| c == chunk) {
+                Some(i) => {
+                    unmatched.swap_remove(i);
+                }
+                None => return false,
+            }
+        }
+        unmatched.is_empty()
     }
 
-    #[test]
-    fn test_invalid_header() {
-        let chunk_bytes: Vec<u8> = testing_chunks()
-            .into_iter()
-            .flat_map(|chunk| chunk.as_bytes())
-            .collect();
+    /// Write this Png to a writer a chunk at a time, producing the same bytes
+    /// as `as_bytes` without building the whole file in memory
+    pub fn write_to<W: Write>(&self, mut w: W) -> Result<()> {
+        if let Some(source) = &self.source {
+            w.write_all(source)?;
+            return Ok(());
+        }
+        w.write_all(&self.header)?;
+        for chunk in self.chunks.iter() {
+            chunk.write_to(&mut w)?;
+        }
+        w.write_all(&self.trailer)?;
+        Ok(())
+    }
 
-        let bytes: Vec<u8> = [13, 80, 78, 71, 13, 10, 26, 10]
-            .iter()
-            .chain(chunk_bytes.iter())
-            .copied()
-            .collect();
+    /// Write this Png to an async writer a chunk at a time, producing the
+    /// same bytes as `write_to`
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W>(&self, mut w: W) -> Result<()>
+    where
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        use tokio::io::AsyncWriteExt;
 
-        let png = Png::try_from(bytes.as_ref());
+        if let Some(source) = &self.source {
+            w.write_all(source).await?;
+            return Ok(());
+        }
+        w.write_all(&self.header).await?;
+        for chunk in self.chunks.iter() {
+            chunk.write_to_async(&mut w).await?;
+        }
+        w.write_all(&self.trailer).await?;
+        Ok(())
+    }
 
-        assert!(png.is_err());
+    /// Write this Png with every chunk keeping the checksum it was parsed
+    /// with, reproducing the source bytes exactly even if they were corrupt
+    /// (unlike `as_bytes`, which always recomputes checksums)
+    pub fn write_to_preserving_crcs<W: Write>(&self, mut w: W) -> Result<()> {
+        w.write_all(&self.header)?;
+        for chunk in self.chunks.iter() {
+            w.write_all(&chunk.as_bytes_preserving_crc())?;
+        }
+        w.write_all(&self.trailer)?;
+        Ok(())
     }
 
-    #[test]
+    /// Get this Png as a vector of raw bytes, recomputing every checksum from
+    /// the chunk data (which fixes any corrupt checksums)
+    pub fn as_bytes(&self) -> Vec<u8> {
+        if let Some(source) = &self.source {
+            return source.clone();
+        }
+        let mut bytes = vec![0u8; self.total_len()];
+        bytes[..self.header.len()].copy_from_slice(&self.header);
+        let mut offset = self.header.len();
+        for chunk in &self.chunks {
+            offset += chunk
+                .write_to_slice(&mut bytes[offset..])
+                .expect("buffer is sized by total_len");
+        }
+        bytes[offset..].copy_from_slice(&self.trailer);
+        bytes
+    }
+
+    /// Get the number of bytes this PNG takes up when serialized
+    pub fn total_len(&self) -> usize {
+        let chunks_len: usize = self.chunks.iter().map(Chunk::total_len).sum();
+        self.header.len() + chunks_len + self.trailer.len()
+    }
+
+    /// Replace the text of every tEXt chunk with a given keyword,
+    /// returning how many chunks were updated
+    pub fn update_text(&mut self, keyword: &str, new_text: &str) -> Result<usize> {
+        // find every match before touching anything, so an unreadable tEXt
+        // chunk leaves the Png unchanged
+        let mut matches = Vec::new();
+        for (i, chunk) in self.chunks.iter().enumerate() {
+            if chunk.chunk_type().to_string() != TextChunk::CHUNK_TYPE {
+                continue;
+            }
+            if TextChunk::try_from(chunk)?.keyword() == keyword {
+                matches.push(i);
+            }
+        }
+        if matches.is_empty() {
+            return Ok(0);
+        }
+
+        let text = TextChunk::new(keyword, new_text)?;
+        let chunks = self.chunks_mut();
+        for &i in &matches {
+            chunks[i] = text.to_chunk();
+        }
+        Ok(matches.len())
+    }
+
+    /// Try to read every ancillary chunk as a message, reporting which ones
+    /// hold text and which hold binary data
+    pub fn scan_messages(&self) -> Vec<MessageEntry> {
+        self.chunks
+            .iter()
+            .filter(|chunk| !chunk.is_critical())
+            .map(|chunk| {
+                let text = chunk.data_as_string().ok().filter(|text| {
+                    !text
+                        .chars()
+                        .any(|c| c.is_control() && !c.is_ascii_whitespace())
+                });
+                MessageEntry {
+                    chunk_type: *chunk.chunk_type(),
+                    length: chunk.length(),
+                    is_text: text.is_some(),
+                    text: text
+                        .unwrap_or_else(|| format!("<{} bytes of binary data>", chunk.length())),
+                }
+            })
+            .collect()
+    }
+
+    /// List the index and data entropy of every chunk whose entropy is above
+    /// `threshold` bits per byte, which suggests compressed or encrypted data
+    pub fn high_entropy_chunks(&self, threshold: f64) -> Vec<(usize, f64)> {
+        self.chunks
+            .iter()
+            .map(Chunk::data_entropy)
+            .enumerate()
+            .filter(|&(_, entropy)| entropy > threshold)
+            .collect()
+    }
+
+    /// Apply `f` to every chunk, then check that the result is still valid.
+    /// The changes are kept even if it isn't, so the error only reports
+    /// what went wrong
+    pub fn map_chunks_mut(&mut self, f: impl FnMut(&mut Chunk)) -> Result<()> {
+        self.chunks_mut().iter_mut().for_each(f);
+        self.validate()
+    }
+
+    /// Change the type of the chunk at `index`, keeping its data; the
+    /// checksum is recomputed for the new type
+    pub fn retag_chunk(&mut self, index: usize, new_type: ChunkType) -> Result<()> {
+        let chunk = self
+            .chunks_mut()
+            .get_mut(index)
+            .ok_or_else(|| error_from(&format!("no chunk at index {}", index)))?;
+        *chunk = Chunk::new(new_type, std::mem::take(chunk.data_mut()));
+        Ok(())
+    }
+
+    /// Keep only the chunks matching a predicate, like `Vec::retain`. Unlike
+    /// `extract_where` this can drop any chunk, including IHDR and IEND
+    pub fn retain(&mut self, f: impl FnMut(&Chunk) -> bool) {
+        self.chunks_mut().retain(f);
+    }
+
+    /// Remove and return every chunk matching a predicate, except for the
+    /// IHDR and IEND chunks which are always kept
+    pub fn extract_where(&mut self, mut predicate: impl FnMut(&Chunk) -> bool) -> Vec<Chunk> {
+        let (extracted, kept) = std::mem::take(self.chunks_mut())
+            .into_iter()
+            .partition(|chunk| {
+                let chunk_type = chunk.chunk_type().to_string();
+                chunk_type != "IHDR" && chunk_type != "IEND" && predicate(chunk)
+            });
+        self.chunks = kept;
+        extracted
+    }
+
+    /// Write this Png in the same layout as Display, with each chunk type
+    /// letter coloured by the property it encodes when `color` is set and
+    /// the `color` feature is enabled (callers should pass false when the
+    /// output is not a terminal)
+    pub fn print_colored(&self, w: &mut impl Write, color: bool) -> io::Result<()> {
+        if !color {
+            return write!(w, "{}", self);
+        }
+        writeln!(w, "Png {{",)?;
+        writeln!(w, "  Header: {:?}", self.header())?;
+        writeln!(w, "  Chunks: [")?;
+        for (i, chunk) in self.chunks.iter().enumerate() {
+            writeln!(
+                w,
+                "    {}: Chunk {{Length: {}, Type: {}, Crc: {}}}",
+                i,
+                chunk.length(),
+                colored_chunk_type(chunk.chunk_type()),
+                chunk.crc()
+            )?;
+        }
+        writeln!(w, "  ]")?;
+        write!(w, "}}",)?;
+        Ok(())
+    }
+
+    /// Write a classic offset/hex/ASCII dump of `as_bytes`, 16 bytes per
+    /// line, noting the signature and the start of each chunk in the margin
+    pub fn hexdump(&self, w: &mut impl Write) -> io::Result<()> {
+        const LINE_WIDTH: usize = 16;
+        let bytes = self.as_bytes();
+        let mut boundaries = self.offsets().zip(&self.chunks).peekable();
+        for (line, row) in bytes.chunks(LINE_WIDTH).enumerate() {
+            let start = line * LINE_WIDTH;
+            write!(w, "{:08x}  ", start)?;
+            for i in 0..LINE_WIDTH {
+                match row.get(i) {
+                    Some(byte) => write!(w, "{:02x} ", byte)?,
+                    None => write!(w, "   ")?,
+                }
+                if i == LINE_WIDTH / 2 - 1 {
+                    write!(w, " ")?;
+                }
+            }
+            let ascii: String = row
+                .iter()
+                .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+                .collect();
+            write!(w, " |{:<width$}|", ascii, width = LINE_WIDTH)?;
+
+            let mut notes: Vec<String> = Vec::new();
+            if start == 0 {
+                notes.push("signature".to_owned());
+            }
+            while let Some((offset, chunk)) =
+                boundaries.next_if(|(offset, _)| *offset < start + LINE_WIDTH)
+            {
+                notes.push(format!("{} @{:x}", chunk.chunk_type(), offset));
+            }
+            if notes.is_empty() {
+                writeln!(w)?;
+            } else {
+                writeln!(w, "  {}", notes.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Load a PNG image from a hex string, ignoring any whitespace
+    pub fn from_hex(s: &str) -> Result<Self> {
+        let digits: Vec<u8> = s.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+        assert_or_err(digits.len().is_multiple_of(2), "hex input has an odd number of digits")?;
+        let bytes = digits
+            .chunks_exact(2)
+            .map(|pair| {
+                let digit = |b: u8| (b as char).to_digit(16);
+                match (digit(pair[0]), digit(pair[1])) {
+                    (Some(high), Some(low)) => Ok((high * 16 + low) as u8),
+                    _ => Err(error_from("hex input contains a non-hex digit")),
+                }
+            })
+            .collect::<Result<Vec<u8>>>()?;
+        Png::try_from(bytes.as_slice())
+    }
+
+    /// Get this Png as a lowercase hex string
+    pub fn to_hex(&self) -> String {
+        self.as_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Load a PNG image from a base64 string
+    #[cfg(feature = "base64")]
+    pub fn from_base64(s: &str) -> Result<Self> {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+        let bytes = STANDARD.decode(s.trim())?;
+        Png::try_from(bytes.as_slice())
+    }
+
+    /// Get this Png as a base64 string
+    #[cfg(feature = "base64")]
+    pub fn to_base64(&self) -> String {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+        STANDARD.encode(self.as_bytes())
+    }
+
+    /// Hide a message in a new chunk, picking an unused ancillary, private,
+    /// safe-to-copy chunk type (trying ruSt, then rvSt, ...) and returning it
+    pub fn hide_note(&mut self, message: &str) -> Result<ChunkType> {
+        let offset = |letter: u8, step: u8| b'a' + (letter - b'a' + step) % 26;
+        let chunk_type = (0..26u8)
+            .flat_map(|i| (0..26u8).map(move |j| [offset(b'r', i), offset(b'u', j), b'S', b't']))
+            .filter_map(|bytes| ChunkType::try_from(bytes).ok())
+            .find(|chunk_type| self.chunks.iter().all(|c| c.chunk_type() != chunk_type))
+            .ok_or_else(|| error_from("no unused chunk type left to hide a note in"))?;
+        self.extend([Chunk::new(chunk_type, message.as_bytes().to_vec())]);
+        Ok(chunk_type)
+    }
+
+    /// Store a message in a new chunk of the given type, placed before IEND
+    pub fn encode_message(&mut self, chunk_type: &str, message: &str) -> Result<()> {
+        self.encode_message_typed(chunk_type.parse()?, message);
+        Ok(())
+    }
+
+    /// Store a message in a new chunk of an already parsed type, placed
+    /// before IEND
+    pub fn encode_message_typed(&mut self, chunk_type: ChunkType, message: &str) {
+        self.extend([Chunk::new(chunk_type, message.as_bytes().to_vec())]);
+    }
+
+    /// Copy the ancillary chunks of `source` that `filter` accepts into this
+    /// Png, each placed where the spec's ordering rules allow, returning a log
+    /// of what was copied or skipped. Singleton types this Png already has are
+    /// skipped, and so are unsafe-to-copy chunks unless both images have the
+    /// same IHDR and PLTE, since those chunks may depend on them
+    pub fn copy_ancillary_from(
+        &mut self,
+        source: &Png,
+        mut filter: impl FnMut(&Chunk) -> bool,
+    ) -> Vec<String> {
+        let same_critical = ["IHDR", "PLTE"].iter().all(|t| {
+            let data = |png: &Png| png.chunk_by_type(t).map(|c| c.data().to_vec());
+            data(self) == data(source)
+        });
+        let mut log = Vec::new();
+        for chunk in source.chunks.iter() {
+            let chunk_type = chunk.chunk_type();
+            if chunk_type.is_critical() || !filter(chunk) {
+                continue;
+            }
+            if !chunk_type.is_safe_to_copy() && !same_critical {
+                log.push(format!(
+                    "skipped unsafe-to-copy {} chunk, as the critical chunks differ",
+                    chunk_type
+                ));
+                continue;
+            }
+            if let Err(err) = self.check_not_duplicate(chunk) {
+                log.push(format!("skipped {} chunk: {}", chunk_type, err));
+                continue;
+            }
+            let index = self.ancillary_insert_index(chunk_type);
+            self.chunks_mut().insert(index, chunk.clone_with_type(*chunk_type));
+            log.push(format!("copied {} chunk to index {}", chunk_type, index));
+        }
+        log
+    }
+
+    /// Replace every tEXt chunk with more than `min_size` bytes of data with an
+    /// equivalent zTXt chunk, returning how many chunks were compressed
+    #[cfg(feature = "compression")]
+    pub fn compress_text_chunks(&mut self, min_size: usize) -> Result<usize> {
+        // convert every chunk before swapping any in, so that a failure
+        // leaves the Png unchanged
+        let mut converted = Vec::new();
+        for (i, chunk) in self.chunks.iter().enumerate() {
+            if chunk.chunk_type().to_string() == TextChunk::CHUNK_TYPE
+                && chunk.data().len() > min_size
+            {
+                converted.push((i, TextChunk::try_from(chunk)?.to_compressed_chunk()?));
+            }
+        }
+        Ok(self.replace_chunks(converted))
+    }
+
+    /// Replace every zTXt chunk with an equivalent tEXt chunk, returning how
+    /// many chunks were decompressed
+    #[cfg(feature = "compression")]
+    pub fn decompress_text_chunks(&mut self) -> Result<usize> {
+        let mut converted = Vec::new();
+        for (i, chunk) in self.chunks.iter().enumerate() {
+            if chunk.chunk_type().to_string() == TextChunk::COMPRESSED_CHUNK_TYPE {
+                converted.push((i, TextChunk::from_compressed_chunk(chunk)?.to_chunk()));
+            }
+        }
+        Ok(self.replace_chunks(converted))
+    }
+
+    /// Attach a zlib-compressed ICC profile in an iCCP chunk, replacing any
+    /// existing one or else inserting it before PLTE and IDAT
+    #[cfg(feature = "compression")]
+    pub fn set_icc_profile(&mut self, name: &str, profile: &[u8]) -> Result<()> {
+        let mut data = text::encode_keyword(name)?;
+        // separator, then compression method 0 (zlib)
+        data.extend([0, 0]);
+        data.extend(compression::compress(profile)?);
+        let chunk = Chunk::new(ChunkType::from_ascii_str("iCCP")?, data);
+
+        let existing = self.indices_of_type("iCCP").first().copied();
+        let before = self
+            .chunks
+            .iter()
+            .position(|c| matches!(c.chunk_type().as_bytes(), b"PLTE" | b"IDAT"));
+        match (existing, before) {
+            (Some(i), _) => self.chunks_mut()[i] = chunk,
+            (None, Some(i)) => self.chunks_mut().insert(i, chunk),
+            (None, None) => self.extend([chunk]),
+        }
+        Ok(())
+    }
+
+    /// Get the decompressed ICC profile from the iCCP chunk, if there is one
+    #[cfg(feature = "compression")]
+    pub fn icc_profile(&self) -> Result<Option<Vec<u8>>> {
+        let chunk = match self.chunk_by_type("iCCP") {
+            Some(chunk) => chunk,
+            None => return Ok(None),
+        };
+        let (_, rest) = text::split_keyword(chunk.data())?;
+        let (&method, compressed) = rest
+            .split_first()
+            .ok_or_else(|| error_from("invalid iCCP chunk (missing compression method)"))?;
+        assert_or_err(method == 0, "invalid iCCP chunk (unknown compression method)")?;
+        Ok(Some(compression::decompress(compressed)?))
+    }
+
+    /// Combine the (contiguous) IDAT chunks into a single IDAT chunk in the
+    /// same position
+    pub fn merge_idat(&mut self) -> Result<()> {
+        self.resplit_idat(usize::MAX)
+    }
+
+    /// Split the image data across contiguous IDAT chunks holding at most
+    /// `max_size` bytes each, merging the existing IDAT chunks first
+    pub fn split_idat(&mut self, max_size: usize) -> Result<()> {
+        assert_or_err(max_size > 0, "IDAT chunks must hold at least 1 byte")?;
+        self.resplit_idat(max_size)
+    }
+
+    /// Replace the run of IDAT chunks with new ones of at most `max_size`
+    /// bytes, erroring if the IDAT chunks are not contiguous
+    fn resplit_idat(&mut self, max_size: usize) -> Result<()> {
+        let indices = self.indices_of_type("IDAT");
+        let (first, last) = match (indices.first(), indices.last()) {
+            (Some(&first), Some(&last)) => (first, last),
+            _ => return Ok(()),
+        };
+        assert_or_err(
+            last - first + 1 == indices.len(),
+            "IDAT chunks must be contiguous",
+        )?;
+
+        let chunk_type = *self.chunks[first].chunk_type();
+        let data: Vec<u8> = self.chunks[first..=last]
+            .iter()
+            .flat_map(|c| c.data())
+            .copied()
+            .collect();
+        let mut fragments: Vec<Chunk> = data
+            .chunks(max_size)
+            .map(|piece| Chunk::new(chunk_type, piece.to_vec()))
+            .collect();
+        if fragments.is_empty() {
+            fragments.push(Chunk::new(chunk_type, Vec::new()));
+        }
+        self.chunks_mut().splice(first..=last, fragments);
+        Ok(())
+    }
+
+    /// Get a copy of this PNG with only the chunks needed to display it:
+    /// IHDR, PLTE (only for indexed images), the IDAT chunks and IEND
+    pub fn minimal(&self) -> Png {
+        // without a readable header, keep any palette to be safe
+        let needs_plte = self.ihdr().map_or(true, |header| header.is_indexed());
+        let mut chunks: Vec<Chunk> = self
+            .chunks
+            .iter()
+            .filter(|chunk| match chunk.chunk_type().as_bytes() {
+                b"IHDR" | b"IDAT" => true,
+                b"PLTE" => needs_plte,
+                _ => false,
+            })
+            .map(|chunk| Chunk::new(*chunk.chunk_type(), chunk.data().to_vec()))
+            .collect();
+        chunks.push(Chunk::new_iend());
+        Png::from_chunks(chunks)
+    }
+
+    /// Remove later copies of ancillary chunks with the same type and data as
+    /// an earlier one, returning how many chunks were removed
+    pub fn dedup_ancillary(&mut self) -> usize {
+        let mut seen: HashSet<&Chunk> = HashSet::new();
+        let keep: Vec<bool> = self
+            .chunks
+            .iter()
+            .map(|chunk| chunk.is_critical() || seen.insert(chunk))
+            .collect();
+
+        let before = self.chunks.len();
+        let mut keep = keep.into_iter();
+        self.chunks_mut().retain(|_| keep.next().unwrap_or(true));
+        before - self.chunks.len()
+    }
+
+    /// Remove everything after the first IEND chunk, both chunks and the
+    /// trailer, returning how many bytes were dropped
+    pub fn truncate_to_iend(&mut self) -> usize {
+        let i = match self.iend_index() {
+            Some(i) => i,
+            None => return 0,
+        };
+        let dropped: usize = self
+            .chunks_mut()
+            .drain(i + 1..)
+            .map(|chunk| REQ_FIELDS_WIDTH + chunk.data().len())
+            .sum();
+        dropped + self.strip_trailer().len()
+    }
+
+    /// Fix the common defects that make a PNG invalid, returning a log of
+    /// every change made: ancillary type codes with an invalid reserved bit
+    /// are renamed, length fields that don't match the data are rewritten,
+    /// stale checksums are recomputed, anything after IEND is removed and a
+    /// missing IEND is added
+    pub fn repair(&mut self) -> Vec<String> {
+        let mut log = Vec::new();
+
+        for i in 0..self.chunks.len() {
+            let chunk_type = *self.chunks[i].chunk_type();
+            if chunk_type.is_critical() || chunk_type.is_reserved_bit_valid() {
+                continue;
+            }
+            let mut bytes = chunk_type.bytes();
+            bytes[2].make_ascii_uppercase();
+            let fixed = ChunkType::try_from(bytes).expect("changing case keeps a type valid");
+            if self.retag_chunk(i, fixed).is_ok() {
+                log.push(format!(
+                    "renamed {} chunk at index {} to {}",
+                    chunk_type, i, fixed
+                ));
+            }
+        }
+
+        for (i, chunk) in self.chunks_mut().iter_mut().enumerate() {
+            let length = chunk.length();
+            if length as usize != chunk.data().len() {
+                let fixed = chunk.refresh_length();
+                log.push(format!(
+                    "rewrote length of {} chunk at index {} from {} to {}",
+                    chunk.chunk_type(),
+                    i,
+                    length,
+                    fixed
+                ));
+            }
+            if chunk.has_stale_crc() {
+                chunk.refresh_crc();
+                log.push(format!(
+                    "recomputed checksum of {} chunk at index {}",
+                    chunk.chunk_type(),
+                    i
+                ));
+            }
+        }
+
+        let dropped = self.truncate_to_iend();
+        if dropped > 0 {
+            log.push(format!("removed {} byte(s) after IEND", dropped));
+        }
+        if self.iend_index().is_none() {
+            self.chunks_mut().push(Chunk::new_iend());
+            log.push("added missing IEND chunk".to_owned());
+        }
+        log
+    }
+
+    /// Error if `chunk` is a singleton type that this PNG already has
+    fn check_not_duplicate(&self, chunk: &Chunk) -> Result<()> {
+        let chunk_type = chunk.chunk_type().to_string();
+        let is_singleton = Png::SINGLETON_TYPES.contains(&chunk_type.as_str());
+        assert_or_err(
+            !is_singleton || self.chunk_by_type(&chunk_type).is_none(),
+            &format!("png already has a {} chunk", chunk_type),
+        )
+    }
+
+    /// List the ordering violations of `check_ordering`, along with the index
+    /// of the chunk at fault where there is one
+    fn ordering_violations(&self) -> Vec<(Option<usize>, String)> {
+        let mut violations = Vec::new();
+        if self.chunks.first().map(|c| c.chunk_type().as_bytes()) != Some(b"IHDR") {
+            let ihdr = self.indices_of_type("IHDR").first().copied();
+            violations.push((ihdr, "IHDR chunk must be the first chunk".to_owned()));
+        }
+        match self.iend_index() {
+            None => violations.push((None, "no IEND chunk".to_owned())),
+            Some(i) if i + 1 != self.chunks.len() => {
+                violations.push((Some(i), "IEND chunk must be the last chunk".to_owned()))
+            }
+            Some(_) => {}
+        }
+
+        let idats = self.indices_of_type("IDAT");
+        if let Some(&first_idat) = idats.first() {
+            for plte in self.indices_of_type("PLTE") {
+                if plte > first_idat {
+                    let violation = format!("PLTE chunk at index {} must come before IDAT", plte);
+                    violations.push((Some(plte), violation));
+                }
+            }
+        }
+        for pair in idats.windows(2) {
+            if pair[1] != pair[0] + 1 {
+                let violation = format!(
+                    "IDAT chunk at index {} must directly follow the previous IDAT chunk",
+                    pair[1]
+                );
+                violations.push((Some(pair[1]), violation));
+            }
+        }
+
+        violations.extend(
+            self.ancillary_ordering_violations()
+                .into_iter()
+                .map(|(i, violation)| (Some(i), violation)),
+        );
+        violations
+    }
+
+    /// List each ancillary chunk that comes before or after PLTE or IDAT when
+    /// the spec says otherwise, along with its index
+    fn ancillary_ordering_violations(&self) -> Vec<(usize, String)> {
+        let plte = self.indices_of_type("PLTE").first().copied();
+        let idat = self.indices_of_type("IDAT").first().copied();
+        let mut violations = Vec::new();
+        let mut violation = |i: usize, chunk_type: &str, rule: &str| {
+            let message = format!("{} chunk at index {} must come {}", chunk_type, i, rule);
+            violations.push((i, message));
+        };
+
+        for (i, chunk) in self.chunks.iter().enumerate() {
+            let chunk_type = chunk.chunk_type().to_string();
+            let chunk_type = chunk_type.as_str();
+            let constrained = BEFORE_PLTE.contains(&chunk_type)
+                || AFTER_PLTE.contains(&chunk_type)
+                || BEFORE_IDAT.contains(&chunk_type);
+            if !constrained {
+                continue;
+            }
+            if BEFORE_PLTE.contains(&chunk_type) && plte.is_some_and(|p| i > p) {
+                violation(i, chunk_type, "before PLTE");
+            } else if AFTER_PLTE.contains(&chunk_type) && plte.is_some_and(|p| i < p) {
+                violation(i, chunk_type, "after PLTE");
+            } else if idat.is_some_and(|d| i > d) {
+                violation(i, chunk_type, "before IDAT");
+            }
+        }
+        violations
+    }
+
+    /// Parse PNG bytes whose length fields may be wrong, as after careless hex
+    /// editing, then `repair` the result. A length that doesn't lead to the
+    /// start of another chunk is rewritten to end just before the next one
+    /// found. Returns the Png along with a log of every change made
+    pub fn repair_bytes(bytes: &[u8]) -> Result<(Png, Vec<String>)> {
+        if !bytes.starts_with(&Png::STANDARD_HEADER) {
+            return Err(InvalidSignature.into());
+        }
+        let mut log = Vec::new();
+        let mut chunks: Vec<Chunk> = Vec::new();
+        let mut offset = Png::STANDARD_HEADER.len();
+        while offset < bytes.len() {
+            let rest = &bytes[offset..];
+            assert_or_err(rest.len() >= REQ_FIELDS_WIDTH, "invalid png (incomplete chunk)")?;
+            let declared = u32::from_be_bytes(rest[..LENGTH_WIDTH].try_into()?);
+            let type_bytes = &rest[LENGTH_WIDTH..LENGTH_WIDTH + TYPE_WIDTH];
+            let is_iend = type_bytes == b"IEND";
+
+            let declared_end = offset + REQ_FIELDS_WIDTH + declared as usize;
+            let end = if is_iend || is_chunk_boundary(bytes, declared_end) {
+                declared_end
+            } else {
+                // the end of the data always counts as a boundary
+                (offset + REQ_FIELDS_WIDTH..=bytes.len())
+                    .find(|&i| is_chunk_boundary(bytes, i))
+                    .unwrap_or(bytes.len())
+            };
+            let mut chunk_bytes = bytes
+                .get(offset..end)
+                .ok_or_else(|| error_from("invalid png (incomplete chunk)"))?
+                .to_vec();
+            if end != declared_end {
+                let length = (end - offset - REQ_FIELDS_WIDTH) as u32;
+                chunk_bytes[..LENGTH_WIDTH].copy_from_slice(&length.to_be_bytes());
+                log.push(format!(
+                    "rewrote length of {} chunk at index {} from {} to {}",
+                    String::from_utf8_lossy(type_bytes),
+                    chunks.len(),
+                    declared,
+                    length
+                ));
+            }
+            chunks.push(Chunk::try_from_unverified(&chunk_bytes)?);
+            offset = end;
+            if is_iend {
+                break;
+            }
+        }
+
+        let mut png = Png::from_parts(chunks, bytes[offset..].to_vec());
+        log.extend(png.repair());
+        Ok((png, log))
+    }
+
+    /// Find where a new ancillary chunk of a given type can go without breaking
+    /// the ordering rules: as late as allowed, but always before IEND
+    fn ancillary_insert_index(&self, chunk_type: &ChunkType) -> usize {
+        let chunk_type = chunk_type.to_string();
+        let first = |t: &str| self.indices_of_type(t).first().copied();
+        let end = self.iend_index().unwrap_or(self.chunks.len());
+        if BEFORE_PLTE.contains(&chunk_type.as_str()) {
+            first("PLTE").or(first("IDAT")).unwrap_or(end)
+        } else if AFTER_PLTE.contains(&chunk_type.as_str())
+            || BEFORE_IDAT.contains(&chunk_type.as_str())
+        {
+            first("IDAT").unwrap_or(end)
+        } else {
+            end
+        }
+    }
+
+    /// Put each chunk in place of the one at its index, returning how many
+    /// were replaced
+    #[cfg(feature = "compression")]
+    fn replace_chunks(&mut self, replacements: Vec<(usize, Chunk)>) -> usize {
+        if replacements.is_empty() {
+            return 0;
+        }
+        let count = replacements.len();
+        let chunks = self.chunks_mut();
+        for (i, chunk) in replacements {
+            chunks[i] = chunk;
+        }
+        count
+    }
+
+    /// Get mutable access to the chunks, dropping the cached source bytes
+    /// and offsets since they may no longer match
+    fn chunks_mut(&mut self) -> &mut Vec<Chunk> {
+        self.source = None;
+        self.chunk_offsets = None;
+        &mut self.chunks
+    }
+
+    /// Find the index of the first IEND chunk, if any
+    fn iend_index(&self) -> Option<usize> {
+        self.chunks
+            .iter()
+            .position(|c| c.chunk_type().to_string() == "IEND")
+    }
+}
+
+impl ParseOptions {
+    /// Check everything the spec requires, as a validator would
+    pub fn strict() -> Self {
+        ParseOptions {
+            verify_crcs: true,
+            allow_unknown_critical: false,
+            allow_bad_reserved_bit: false,
+            max_chunk_size: Some(MAX_CHUNK_LENGTH),
+            cache_source: false,
+        }
+    }
+
+    /// Skip checksum verification, for reading metadata from many files
+    pub fn fast() -> Self {
+        ParseOptions {
+            verify_crcs: false,
+            ..ParseOptions::default()
+        }
+    }
+
+    /// Parse the raw bytes of the chunk at `index`, checking it against these
+    /// options
+    fn parse_chunk(&self, bytes: &[u8], index: usize) -> Result<Chunk> {
+        // check the size before the data gets copied
+        let length = bytes.len().saturating_sub(REQ_FIELDS_WIDTH);
+        if let Some(max) = self.max_chunk_size {
+            assert_or_err(
+                length as u64 <= u64::from(max),
+                &format!(
+                    "chunk at index {} is too large: {} bytes (max {})",
+                    index, length, max
+                ),
+            )?;
+        }
+        let chunk = match self.verify_crcs {
+            true => Chunk::try_from(bytes)?,
+            false => Chunk::try_from_unverified(bytes)?,
+        };
+        let chunk_type = chunk.chunk_type();
+        assert_or_err(
+            self.allow_unknown_critical
+                || !chunk_type.is_critical()
+                || chunk_type.standard_name().is_some(),
+            &format!("unknown critical chunk {} at index {}", chunk_type, index),
+        )?;
+        assert_or_err(
+            self.allow_bad_reserved_bit || chunk_type.is_reserved_bit_valid(),
+            &format!(
+                "{} chunk at index {} has an invalid reserved bit",
+                chunk_type, index
+            ),
+        )?;
+        Ok(chunk)
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            verify_crcs: true,
+            allow_unknown_critical: true,
+            allow_bad_reserved_bit: true,
+            max_chunk_size: None,
+            cache_source: false,
+        }
+    }
+}
+
+impl<'a> PngRef<'a> {
+    /// Get a list of all chunks in this image
+    pub fn chunks(&self) -> &[ChunkRef<'a>] {
+        &self.chunks
+    }
+
+    /// Get the first chunk of a given type, if any
+    pub fn chunk_by_type(&self, chunk_type: &str) -> Option<&ChunkRef<'a>> {
+        self.chunks
+            .iter()
+            .find(|chunk| chunk.chunk_type().to_string() == chunk_type)
+    }
+
+    /// Get the bytes stored after IEND, usually empty
+    pub fn trailer(&self) -> &'a [u8] {
+        self.trailer
+    }
+
+    /// Copy every chunk into an owned Png
+    pub fn to_owned(&self) -> Png {
+        let chunks = self.chunks.iter().copied().map(ChunkRef::to_owned).collect();
+        Png::from_parts(chunks, self.trailer.to_vec())
+    }
+}
+
+impl<R: Read> PngChunks<R> {
+    /// Read the signature if needed, then the next chunk
+    fn read_next(&mut self) -> Result<Option<Chunk>> {
+        if !self.started {
+            self.started = true;
+            read_signature(&mut self.reader)?;
+        }
+        Chunk::read_from(&mut self.reader)
+    }
+}
+
+impl<R: Read> Iterator for PngChunks<R> {
+    type Item = Result<Chunk>;
+    /// Yields each chunk in turn, ending after IEND, at the end of the
+    /// input, or after the first error
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let next = self.read_next();
+        self.done = match &next {
+            Ok(Some(chunk)) => chunk.chunk_type().to_string() == "IEND",
+            Ok(None) | Err(_) => true,
+        };
+        next.transpose()
+    }
+}
+
+impl Default for Png {
+    /// Gives a minimal valid PNG holding a single transparent pixel, to
+    /// use as a canvas for data
+    fn default() -> Self {
+        let header =
+            ImageHeader::new(1, 1, 8, TRUECOLOR_ALPHA).expect("1x1 RGBA is a valid header");
+        let idat_type = ChunkType::try_from(*b"IDAT").expect("IDAT is a valid chunk type");
+        Png::from_chunks(vec![
+            header.to_chunk(),
+            Chunk::new(idat_type, BLANK_PIXEL_IDAT.to_vec()),
+            Chunk::new_iend(),
+        ])
+    }
+}
+
+impl PartialEq for Png {
+    /// Compares the header and chunks, ignoring whether either Png still
+    /// holds the bytes it was parsed from
+    fn eq(&self, other: &Self) -> bool {
+        self.header == other.header
+            && self.chunks == other.chunks
+            && self.trailer == other.trailer
+    }
+}
+
+impl Eq for Png {}
+
+impl Extend<Chunk> for Png {
+    /// Gives the ability to add chunks from an iterator, keeping them
+    /// ahead of the IEND chunk if there is one
+    fn extend<I: IntoIterator<Item = Chunk>>(&mut self, iter: I) {
+        match self.iend_index() {
+            Some(i) => {
+                let tail = self.chunks_mut().split_off(i);
+                self.chunks_mut().extend(iter);
+                self.chunks_mut().extend(tail);
+            }
+            None => self.chunks_mut().extend(iter),
+        }
+    }
+}
+
+impl FromIterator<Chunk> for Png {
+    /// Gives the ability to collect chunks into a Png
+    fn from_iter<I: IntoIterator<Item = Chunk>>(iter: I) -> Self {
+        Png::from_chunks(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for Png {
+    type Item = Chunk;
+    type IntoIter = vec::IntoIter<Chunk>;
+    /// Gives the ability to iterate over the chunks of a Png by value
+    fn into_iter(self) -> Self::IntoIter {
+        self.chunks.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Png {
+    type Item = &'a Chunk;
+    type IntoIter = slice::Iter<'a, Chunk>;
+    /// Gives the ability to iterate over the chunks of a Png by reference
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Png {
+    type Item = &'a mut Chunk;
+    type IntoIter = slice::IterMut<'a, Chunk>;
+    /// Gives the ability to iterate over the chunks of a Png mutably
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl TryFrom<&[u8]> for Png {
+    type Error = Error;
+    /// Gives the ability to construct a Png from raw bytes
+    fn try_from(bytes: &[u8]) -> Result<Png> {
+        Png::parse_with_options(bytes, &ParseOptions::default())
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for PngRef<'a> {
+    type Error = Error;
+    /// Gives the ability to view a Png in raw bytes without copying it
+    fn try_from(bytes: &'a [u8]) -> Result<Self> {
+        let mut chunks: Vec<ChunkRef> = Vec::new();
+        let trailer = for_each_chunk_slice(bytes, |chunk_bytes| {
+            chunks.push(ChunkRef::try_from(chunk_bytes)?);
+            Ok(())
+        })?;
+        Ok(PngRef {
+            bytes,
+            chunks,
+            trailer,
+        })
+    }
+}
+
+impl Display for Png {
+    /// Gives the ability to format Png as a string
+    /// and Enables ToString
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        writeln!(f, "Png {{",)?;
+        writeln!(f, "  Header: {:?}", self.header())?;
+        writeln!(f, "  Chunks: [")?;
+        for (i, chunk) in self.chunks.iter().enumerate() {
+            writeln!(f, "    {}: {}", i, chunk)?;
+        }
+        writeln!(f, "  ]")?;
+        write!(f, "}}",)?;
+        Ok(())
+    }
+}
+
+/// Check that a file starts with the PNG signature, naming the format it
+/// looks like instead if it is a common one
+fn check_file_signature(bytes: &[u8]) -> Result<()> {
+    const KNOWN_FORMATS: [(&[u8], &str); 8] = [
+        (b"\xff\xd8\xff", "JPEG"),
+        (b"GIF8", "GIF"),
+        (b"BM", "BMP"),
+        (b"II*\0", "TIFF"),
+        (b"MM\0*", "TIFF"),
+        (b"%PDF", "PDF"),
+        (b"PK\x03\x04", "ZIP archive"),
+        (b"\0\0\x01\0", "ICO"),
+    ];
+
+    if bytes.starts_with(&Png::STANDARD_HEADER) {
+        return Ok(());
+    }
+    let is_webp = bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP");
+    let format = KNOWN_FORMATS
+        .iter()
+        .find(|(magic, _)| bytes.starts_with(magic))
+        .map(|&(_, name)| name)
+        .or(is_webp.then_some("WebP"));
+    match format {
+        Some(name) => Err(error_from(&format!("this looks like a {}, not a PNG", name))),
+        None => Err(error_from("not a PNG file")),
+    }
+}
+
+/// Read and check the PNG signature at the start of a stream
+fn read_signature<R: Read>(reader: &mut R) -> Result<()> {
+    let mut header = [0u8; 8];
+    reader.read_exact(&mut header).map_err(|_| InvalidSignature)?;
+    if header != Png::STANDARD_HEADER {
+        return Err(InvalidSignature.into());
+    }
+    Ok(())
+}
+
+/// Tests whether `offset` in raw PNG bytes could be where a chunk starts (or
+/// is the end of the bytes), going by its type code and whether its length
+/// fits in the rest of the bytes
+fn is_chunk_boundary(bytes: &[u8], offset: usize) -> bool {
+    if offset == bytes.len() {
+        return true;
+    }
+    let Some(fields) = bytes.get(offset..offset + LENGTH_WIDTH + TYPE_WIDTH) else {
+        return false;
+    };
+    let length = u32::from_be_bytes([fields[0], fields[1], fields[2], fields[3]]) as usize;
+    fields[LENGTH_WIDTH..].iter().all(u8::is_ascii_alphabetic)
+        && offset + REQ_FIELDS_WIDTH + length <= bytes.len()
+}
+
+/// View the chunk starting at `offset` in raw PNG bytes, checking its checksum
+fn chunk_ref_at(bytes: &[u8], offset: usize) -> Result<ChunkRef<'_>> {
+    let rest = &bytes[offset..];
+    assert_or_err(rest.len() >= REQ_FIELDS_WIDTH, "invalid png (incomplete chunk)")?;
+    let chunk_length = u32::from_be_bytes(rest[..LENGTH_WIDTH].try_into()?) as usize;
+    let chunk_bytes = rest
+        .get(..chunk_length + REQ_FIELDS_WIDTH)
+        .ok_or_else(|| error_from("invalid png (incomplete chunk)"))?;
+    ChunkRef::try_from(chunk_bytes)
+}
+
+/// Split raw PNG bytes into the raw bytes of each chunk, calling `f` on each,
+/// up to and including IEND. Returns whatever follows IEND
+fn for_each_chunk_slice<'a>(
+    bytes: &'a [u8],
+    mut f: impl FnMut(&'a [u8]) -> Result<()>,
+) -> Result<&'a [u8]> {
+    // read the header, erroring if it is not present (file too short)
+    // or incorrect
+    if !bytes.starts_with(&Png::STANDARD_HEADER) {
+        return Err(InvalidSignature.into());
+    }
+    let mut data = &bytes[Png::STANDARD_HEADER.len()..];
+
+    // parse the remainder of the file as PNG chunks
+    loop {
+        // read the length, erroring if it is not present
+        assert_or_err(data.len() >= LENGTH_WIDTH, "invalid png (incomplete chunk)")?;
+        let chunk_length = u32::from_be_bytes(data[..LENGTH_WIDTH].try_into()?) as usize;
+
+        // read the rest of the chunk, erroring if it is incomplete
+        assert_or_err(
+            data.len() >= chunk_length + REQ_FIELDS_WIDTH,
+            "invalid png (incomplete chunk)",
+        )?;
+        let chunk_bytes;
+        // keep splitting the chunks off of data...
+        (chunk_bytes, data) = data.split_at(chunk_length + REQ_FIELDS_WIDTH);
+        f(chunk_bytes)?;
+        // ...until the data is empty or IEND is reached
+        let chunk_type = &chunk_bytes[LENGTH_WIDTH..LENGTH_WIDTH + TYPE_WIDTH];
+        if data.is_empty() || chunk_type == b"IEND" {
+            break;
+        }
+    }
+    Ok(data)
+}
+
+/// Colour each letter of a chunk type: critical in red, private in yellow,
+/// an invalid reserved bit in magenta and safe-to-copy in green
+#[cfg(feature = "color")]
+fn colored_chunk_type(chunk_type: &ChunkType) -> String {
+    use owo_colors::OwoColorize;
+
+    let [a, b, c, d] = chunk_type.bytes().map(char::from);
+    let mut s = String::new();
+    match chunk_type.is_critical() {
+        true => s.push_str(&a.red().to_string()),
+        false => s.push(a),
+    }
+    match chunk_type.is_public() {
+        true => s.push(b),
+        false => s.push_str(&b.yellow().to_string()),
+    }
+    match chunk_type.is_reserved_bit_valid() {
+        true => s.push(c),
+        false => s.push_str(&c.magenta().to_string()),
+    }
+    match chunk_type.is_safe_to_copy() {
+        true => s.push_str(&d.green().to_string()),
+        false => s.push(d),
+    }
+    s
+}
+
+/// Without the `color` feature chunk types are printed as-is
+#[cfg(not(feature = "color"))]
+fn colored_chunk_type(chunk_type: &ChunkType) -> String {
+    chunk_type.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::Chunk;
+    use crate::chunk_type::ChunkType;
+    use std::convert::TryFrom;
+    use std::str::FromStr;
+
+    fn testing_chunks() -> Vec<Chunk> {
+        let mut chunks = Vec::new();
+
+        chunks.push(chunk_from_strings("FrSt", "I am the first chunk").unwrap());
+        chunks.push(chunk_from_strings("miDl", "I am another chunk").unwrap());
+        chunks.push(chunk_from_strings("LASt", "I am the last chunk").unwrap());
+
+        chunks
+    }
+
+    fn testing_png() -> Png {
+        let chunks = testing_chunks();
+        Png::from_chunks(chunks)
+    }
+
+    fn chunk_from_strings(chunk_type: &str, data: &str) -> Result<Chunk> {
+        use std::str::FromStr;
+
+        let chunk_type = ChunkType::from_str(chunk_type)?;
+        let data: Vec<u8> = data.bytes().collect();
+
+        Ok(Chunk::new(chunk_type, data))
+    }
+
+    #[test]
+    fn test_from_chunks() {
+        let chunks = testing_chunks();
+        let png = Png::from_chunks(chunks);
+
+        assert_eq!(png.chunks().len(), 3);
+    }
+
+    #[test]
+    fn test_from_parts_round_trip() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let (chunks, trailer) = png.into_parts();
+        assert!(trailer.is_empty());
+
+        let png = Png::from_parts(chunks, b"extra".to_vec());
+        assert_eq!(png.trailer(), b"extra");
+        let bytes = png.as_bytes();
+        assert_eq!(bytes.len(), png.total_len());
+        assert_eq!(&bytes[..PNG_FILE.len()], &PNG_FILE[..]);
+        assert!(bytes.ends_with(b"extra"));
+
+        let mut written: Vec<u8> = Vec::new();
+        png.write_to_preserving_crcs(&mut written).unwrap();
+        assert_eq!(written, bytes);
+
+        let (chunks, trailer) = png.into_parts();
+        assert_eq!(Png::from_parts(chunks, trailer).as_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_trailer_after_iend() {
+        let mut bytes = PNG_FILE.to_vec();
+        bytes.extend(b"PK\x03\x04 hidden archive");
+        let mut png = Png::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(png.chunks().len(), Png::try_from(&PNG_FILE[..]).unwrap().chunks().len());
+        assert_eq!(png.trailer(), b"PK\x03\x04 hidden archive");
+        assert_eq!(png.as_bytes(), bytes);
+        assert_eq!(Png::from_reader(bytes.as_slice()).unwrap(), png);
+        assert_eq!(PngRef::try_from(bytes.as_slice()).unwrap().trailer(), png.trailer());
+
+        assert_eq!(png.strip_trailer(), b"PK\x03\x04 hidden archive");
+        assert!(png.trailer().is_empty());
+        assert_eq!(png.as_bytes(), PNG_FILE.to_vec());
+    }
+
+    #[test]
+    fn test_valid_from_bytes() {
+        let chunk_bytes: Vec<u8> = testing_chunks()
+            .into_iter()
+            .flat_map(|chunk| chunk.as_bytes())
+            .collect();
+
+        let bytes: Vec<u8> = Png::STANDARD_HEADER
+            .iter()
+            .chain(chunk_bytes.iter())
+            .copied()
+            .collect();
+
+        let png = Png::try_from(bytes.as_ref());
+
+        assert!(png.is_ok());
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        let chunk_bytes: Vec<u8> = testing_chunks()
+            .into_iter()
+            .flat_map(|chunk| chunk.as_bytes())
+            .collect();
+
+        let bytes: Vec<u8> = [13, 80, 78, 71, 13, 10, 26, 10]
+            .iter()
+            .chain(chunk_bytes.iter())
+            .copied()
+            .collect();
+
+        let png = Png::try_from(bytes.as_ref());
+
+        assert!(png.is_err());
+    }
+
+    #[test]
     fn test_invalid_chunk() {
         let mut chunk_bytes: Vec<u8> = testing_chunks()
             .into_iter()
             .flat_map(|chunk| chunk.as_bytes())
             .collect();
 
-        #[rustfmt::skip]
-        let mut bad_chunk = vec![
-            0, 0, 0, 5,         // length
-            32, 117, 83, 116,   // Chunk Type (bad)
-            65, 64, 65, 66, 67, // Data
-            1, 2, 3, 4, 5       // CRC (bad)
+        #[rustfmt::skip]
+        let mut bad_chunk = vec![
+            0, 0, 0, 5,         // length
+            32, 117, 83, 116,   // Chunk Type (bad)
+            65, 64, 65, 66, 67, // Data
+            1, 2, 3, 4, 5       // CRC (bad)
+        ];
+
+        chunk_bytes.append(&mut bad_chunk);
+
+        let png = Png::try_from(chunk_bytes.as_ref());
+
+        assert!(png.is_err());
+    }
+
+    #[test]
+    fn test_list_chunks() {
+        let png = testing_png();
+        let chunks = png.chunks();
+        assert_eq!(chunks.len(), 3);
+    }
+
+    #[test]
+    fn test_chunk_by_type() {
+        let png = testing_png();
+        let chunk = png.chunk_by_type("FrSt").unwrap();
+        assert_eq!(&chunk.chunk_type().to_string(), "FrSt");
+        assert_eq!(&chunk.data_as_string().unwrap(), "I am the first chunk");
+    }
+
+    #[test]
+    fn test_chunk_by_type_ignore_case() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.encode_message("ruSt", "hidden").unwrap();
+        assert!(png.chunk_by_type("RUST").is_none());
+
+        let chunk = png.chunk_by_type_ignore_case("RUST").unwrap();
+        assert_eq!(&chunk.chunk_type().to_string(), "RuSt");
+        assert!(png.chunk_by_type_ignore_case("nope").is_none());
+        assert!(png.chunk_by_type_ignore_case("R5ST").is_none());
+    }
+
+    #[test]
+    fn test_remove_chunk_ignore_case() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert!(png.remove_chunk("rust").is_err());
+        let removed = png.remove_chunk_ignore_case("rust").unwrap();
+        assert_eq!(&removed.chunk_type().to_string(), "RuSt");
+        assert!(png.remove_chunk_ignore_case("rust").is_err());
+    }
+
+    #[test]
+    fn test_chunks_by_type() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.encode_message("RuSt", "second").unwrap();
+        let found: Vec<&[u8]> = png.chunks_by_type("RuSt").map(Chunk::data).collect();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[1], b"second");
+        assert_eq!(png.chunks_by_type("tEXt").count(), 0);
+    }
+
+    #[test]
+    fn test_insert_chunk() {
+        let mut png = testing_png();
+        let chunk = chunk_from_strings("TeSt", "inserted").unwrap();
+        png.insert_chunk(1, chunk).unwrap();
+        assert_eq!(png.indices_of_type("TeSt"), [1]);
+        assert_eq!(png.chunks().len(), 4);
+        png.insert_chunk(4, chunk_from_strings("EnDs", "").unwrap()).unwrap();
+        assert_eq!(png.indices_of_type("EnDs"), [4]);
+        assert!(png.insert_chunk(6, Chunk::new_iend()).is_err());
+    }
+
+    #[test]
+    fn test_insert_chunk_at_position() {
+        let mut png = png_from_types(&["IHDR", "IDAT", "IDAT", "IEND"]);
+        for (position, chunk_type) in [
+            (InsertPosition::BeforeIEND, "tEXt"),
+            (InsertPosition::BeforeIDAT, "pHYs"),
+            (InsertPosition::AfterIHDR, "gAMA"),
+            (InsertPosition::AtIndex(0), "ruSt"),
+        ] {
+            let chunk = chunk_from_strings(chunk_type, "").unwrap();
+            png.insert_chunk_at_position(position, chunk).unwrap();
+        }
+        let types: Vec<String> = png.iter().map(|c| c.chunk_type().to_string()).collect();
+        assert_eq!(types, ["ruSt", "IHDR", "gAMA", "pHYs", "IDAT", "IDAT", "tEXt", "IEND"]);
+
+        let mut png = png_from_types(&["IHDR", "IEND"]);
+        let chunk = chunk_from_strings("pHYs", "").unwrap();
+        let err = png
+            .insert_chunk_at_position(InsertPosition::BeforeIDAT, chunk)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "no IDAT chunk to insert next to");
+        let chunk = chunk_from_strings("tEXt", "").unwrap();
+        assert!(png.insert_chunk_at_position(InsertPosition::AtIndex(3), chunk).is_err());
+        assert_eq!(png.chunks().len(), 2);
+    }
+
+    #[test]
+    fn test_remove_first_and_all_chunks() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("miDl", "again").unwrap());
+        png.append_chunk(chunk_from_strings("miDl", "and again").unwrap());
+
+        let first = png.remove_first_chunk("miDl").unwrap();
+        assert_eq!(first.data(), b"I am another chunk");
+        let rest = png.remove_all_chunks("miDl");
+        let data: Vec<&[u8]> = rest.iter().map(Chunk::data).collect();
+        assert_eq!(data, [&b"again"[..], &b"and again"[..]]);
+        assert_eq!(png.chunks().len(), 2);
+        assert!(png.remove_all_chunks("miDl").is_empty());
+        assert!(png.remove_first_chunk("miDl").is_err());
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let mut png = testing_png();
+        let mut types = Vec::new();
+        for chunk in &png {
+            types.push(chunk.chunk_type().to_string());
+        }
+        assert_eq!(types, ["FrSt", "miDl", "LASt"]);
+        assert_eq!(png.iter().count(), 3);
+
+        for chunk in &mut png {
+            chunk.data_mut().truncate(4);
+        }
+        assert!(png.iter_mut().all(|chunk| chunk.data() == b"I am"));
+
+        let chunks: Vec<Chunk> = png.into_iter().collect();
+        assert_eq!(chunks.len(), 3);
+    }
+
+    #[test]
+    fn test_append_chunk() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("TeSt", "Message").unwrap());
+        let chunk = png.chunk_by_type("TeSt").unwrap();
+        assert_eq!(&chunk.chunk_type().to_string(), "TeSt");
+        assert_eq!(&chunk.data_as_string().unwrap(), "Message");
+    }
+
+    #[test]
+    fn test_remove_chunk() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("TeSt", "Message").unwrap());
+        png.remove_chunk("TeSt").unwrap();
+        let chunk = png.chunk_by_type("TeSt");
+        assert!(chunk.is_none());
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("IEND", "").unwrap());
+        png.extend(testing_chunks());
+        assert_eq!(png.chunks().len(), 7);
+        assert_eq!(&png.chunks()[3].chunk_type().to_string(), "FrSt");
+        assert_eq!(&png.chunks()[6].chunk_type().to_string(), "IEND");
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let png: Png = testing_chunks().into_iter().collect();
+        assert_eq!(png.chunks().len(), 3);
+        assert_eq!(png.header(), &Png::STANDARD_HEADER);
+    }
+
+    #[test]
+    fn test_update_text() {
+        let mut png = testing_png();
+        png.append_chunk(TextChunk::new("Title", "Hello").unwrap().to_chunk());
+        png.append_chunk(TextChunk::new("Author", "Me").unwrap().to_chunk());
+        png.append_chunk(TextChunk::new("Title", "Hello again").unwrap().to_chunk());
+
+        assert_eq!(png.update_text("Title", "Bonjour").unwrap(), 2);
+
+        let texts: Vec<TextChunk> = png.chunks()[3..]
+            .iter()
+            .map(|c| TextChunk::try_from(c).unwrap())
+            .collect();
+        assert_eq!(texts[0], TextChunk::new("Title", "Bonjour").unwrap());
+        assert_eq!(texts[1], TextChunk::new("Author", "Me").unwrap());
+        assert_eq!(texts[2], TextChunk::new("Title", "Bonjour").unwrap());
+    }
+
+    #[test]
+    fn test_update_text_unchanged_on_error() {
+        let mut png = testing_png();
+        png.append_chunk(TextChunk::new("Title", "Hello").unwrap().to_chunk());
+        png.append_chunk(chunk_from_strings("tEXt", "no separator").unwrap());
+        let before = png.as_bytes();
+
+        assert!(png.update_text("Title", "Bonjour").is_err());
+        assert_eq!(png.as_bytes(), before);
+    }
+
+    #[test]
+    fn test_parse_into_reuses_vector() {
+        let mut reuse = Vec::new();
+
+        Png::parse_into(&PNG_FILE, &mut reuse).unwrap();
+        let first_len = reuse.len();
+        assert_eq!(&reuse[0].chunk_type().to_string(), "IHDR");
+
+        let bytes = testing_png().as_bytes();
+        Png::parse_into(&bytes, &mut reuse).unwrap();
+        assert_ne!(reuse.len(), first_len);
+        assert_eq!(reuse.len(), 3);
+        assert_eq!(&reuse[0].chunk_type().to_string(), "FrSt");
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_icc_profile_round_trip() {
+        let mut png = png_from_types(&["IHDR", "PLTE", "IDAT", "IEND"]);
+        assert_eq!(png.icc_profile().unwrap(), None);
+
+        let profile = b"fake ICC profile ".repeat(8);
+        png.set_icc_profile("Fake RGB", &profile).unwrap();
+        assert_eq!(png.indices_of_type("iCCP"), [1]);
+        assert!(png.chunks()[1].data_starts_with(b"Fake RGB\0\0"));
+        assert_eq!(png.icc_profile().unwrap(), Some(profile));
+        assert!(png.validate().is_ok());
+
+        png.set_icc_profile("Other", b"replacement").unwrap();
+        assert_eq!(png.indices_of_type("iCCP"), [1]);
+        assert_eq!(png.icc_profile().unwrap(), Some(b"replacement".to_vec()));
+        assert!(png.set_icc_profile("", b"nameless").is_err());
+    }
+
+    #[test]
+    fn test_encode_message_typed() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        png.encode_message_typed(chunk_type, "typed message");
+        png.encode_message("ruSt", "parsed message").unwrap();
+        assert!(png.encode_message("ru5t", "oops").is_err());
+
+        let found: Vec<String> = png
+            .indices_of_type("ruSt")
+            .into_iter()
+            .map(|i| png.chunks()[i].data_as_string().unwrap())
+            .collect();
+        assert_eq!(found, ["typed message", "parsed message"]);
+        assert!(png.validate().is_ok());
+    }
+
+    #[test]
+    fn test_scan_messages() {
+        let mut png = png_from_types(&["IHDR", "IDAT", "IEND"]);
+        png.extend([
+            chunk_from_strings("ruSt", "hello\nworld").unwrap(),
+            Chunk::new(ChunkType::from_str("biNy").unwrap(), vec![0xff, 0, 1, 2]),
+        ]);
+
+        let entries = png.scan_messages();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(&entries[0].chunk_type.to_string(), "ruSt");
+        assert_eq!(entries[0].length, 11);
+        assert!(entries[0].is_text);
+        assert_eq!(entries[0].text, "hello\nworld");
+        assert_eq!(&entries[1].chunk_type.to_string(), "biNy");
+        assert!(!entries[1].is_text);
+        assert_eq!(entries[1].text, "<4 bytes of binary data>");
+    }
+
+    #[test]
+    fn test_high_entropy_chunks() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let idat = png.indices_of_type("IDAT")[0];
+        let found = png.high_entropy_chunks(7.0);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, idat);
+        assert!(png.high_entropy_chunks(8.0).is_empty());
+    }
+
+    #[test]
+    fn test_split_and_merge_idat() {
+        let original = Png::try_from(&PNG_FILE[..]).unwrap();
+        let idat_data = original.chunk_by_type("IDAT").unwrap().data().to_vec();
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+
+        png.split_idat(100).unwrap();
+        let indices = png.indices_of_type("IDAT");
+        assert_eq!(indices.len(), idat_data.len().div_ceil(100));
+        assert!(indices.iter().all(|&i| png.chunks()[i].data().len() <= 100));
+        let rejoined: Vec<u8> = indices
+            .iter()
+            .flat_map(|&i| png.chunks()[i].data())
+            .copied()
+            .collect();
+        assert_eq!(rejoined, idat_data);
+        assert!(png.validate().is_ok());
+
+        png.merge_idat().unwrap();
+        assert_eq!(png, original);
+        assert!(png.split_idat(0).is_err());
+    }
+
+    #[test]
+    fn test_merge_idat_not_contiguous() {
+        let mut png = png_from_types(&["IHDR", "IDAT", "tEXt", "IDAT", "IEND"]);
+        assert_eq!(
+            png.merge_idat().unwrap_err().to_string(),
+            "IDAT chunks must be contiguous"
+        );
+        let mut png = png_from_types(&["IHDR", "IEND"]);
+        assert!(png.merge_idat().is_ok());
+        assert_eq!(png.chunks().len(), 2);
+    }
+
+    #[test]
+    fn test_minimal() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.extend([chunk_from_strings("PLTE", "abc").unwrap()]);
+        let minimal = png.minimal();
+        assert!(minimal.validate().is_ok());
+        assert!(minimal.chunks().iter().all(Chunk::is_critical));
+        let types: Vec<String> = minimal
+            .chunks()
+            .iter()
+            .map(|c| c.chunk_type().to_string())
+            .collect();
+        // the image is RGBA, so the palette is only a suggestion
+        assert_eq!(types, ["IHDR", "IDAT", "IEND"]);
+        assert_eq!(minimal.chunk_by_type("IDAT"), png.chunk_by_type("IDAT"));
+    }
+
+    #[test]
+    fn test_minimal_indexed() {
+        let header = ImageHeader::new(1, 1, 8, crate::header::INDEXED).unwrap();
+        let mut png = png_from_types(&["gAMA", "PLTE", "tRNS", "IDAT", "tEXt", "IEND"]);
+        png.chunks_mut().insert(0, header.to_chunk());
+        let types: Vec<String> = png
+            .minimal()
+            .chunks()
+            .iter()
+            .map(|c| c.chunk_type().to_string())
+            .collect();
+        assert_eq!(types, ["IHDR", "PLTE", "IDAT", "IEND"]);
+    }
+
+    #[test]
+    fn test_map_chunks_mut() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.extend([TextChunk::new("Title", "a picture").unwrap().to_chunk()]);
+        png.map_chunks_mut(|chunk| {
+            if chunk.chunk_type().to_string() == TextChunk::CHUNK_TYPE {
+                chunk.data_mut().make_ascii_uppercase();
+            }
+        })
+        .unwrap();
+        let text = TextChunk::try_from(png.chunk_by_type("tEXt").unwrap()).unwrap();
+        assert_eq!(text.to_string(), "TITLE: A PICTURE");
+
+        let err = png
+            .map_chunks_mut(|chunk| {
+                if chunk.chunk_type().to_string() == "IEND" {
+                    chunk.data_mut().push(0);
+                }
+            })
+            .unwrap_err();
+        assert_eq!(err.to_string(), "IEND chunk must not contain data");
+    }
+
+    #[test]
+    fn test_retag_chunk() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let index = png.indices_of_type("RuSt")[0];
+        let data = png.chunks()[index].data().to_vec();
+
+        png.retag_chunk(index, ChunkType::from_str("ruSt").unwrap()).unwrap();
+        let chunk = &png.chunks()[index];
+        assert_eq!(&chunk.chunk_type().to_string(), "ruSt");
+        assert_eq!(chunk.data(), data.as_slice());
+        assert_eq!(chunk.stored_crc(), None);
+        let expected = Chunk::new(ChunkType::from_str("ruSt").unwrap(), data);
+        assert_eq!(chunk.crc(), expected.crc());
+
+        let reparsed = Png::try_from(png.as_bytes().as_slice()).unwrap();
+        assert_eq!(reparsed, png);
+        assert!(png.retag_chunk(99, ChunkType::from_str("ruSt").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.retain(Chunk::is_critical);
+        let types: Vec<String> = png.iter().map(|c| c.chunk_type().to_string()).collect();
+        assert_eq!(types, ["IHDR", "IDAT", "RuSt", "IEND"]);
+
+        let mut png = testing_png();
+        png.retain(|chunk| chunk.chunk_type().as_bytes()[0].is_ascii_uppercase());
+        assert_eq!(png.indices_of_type("miDl"), []);
+        assert_eq!(png.chunks().len(), 2);
+    }
+
+    #[test]
+    fn test_extract_where() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let extracted = png.extract_where(|chunk| chunk.length() > 4);
+
+        // only the image data and the pHYs chunk are over the threshold, as
+        // the IHDR chunk is never extracted
+        let types: Vec<String> = extracted
+            .iter()
+            .map(|c| c.chunk_type().to_string())
+            .collect();
+        assert_eq!(types, ["pHYs", "IDAT"]);
+        assert_eq!(png.chunks().len(), 5);
+        assert!(png.chunk_by_type("IHDR").is_some());
+        assert!(png.chunk_by_type("IDAT").is_none());
+    }
+
+    #[test]
+    fn test_extract_where_keeps_ihdr_and_iend() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.extract_where(|_| true);
+        let types: Vec<String> = png
+            .chunks()
+            .iter()
+            .map(|c| c.chunk_type().to_string())
+            .collect();
+        assert_eq!(types, ["IHDR", "IEND"]);
+    }
+
+    #[test]
+    fn test_print_colored_plain() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let mut out: Vec<u8> = Vec::new();
+        png.print_colored(&mut out, false).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(!out.contains('\x1b'));
+        assert_eq!(out, png.to_string());
+    }
+
+    #[test]
+    fn test_total_len() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert_eq!(png.total_len(), PNG_FILE.len());
+        assert_eq!(png.as_bytes(), PNG_FILE.to_vec());
+    }
+
+    #[test]
+    fn test_write_to() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let mut out: Vec<u8> = Vec::new();
+        png.write_to(&mut out).unwrap();
+        assert_eq!(out, PNG_FILE.to_vec());
+
+        png.append_chunk(chunk_from_strings("ruSt", "new").unwrap());
+        let mut out: Vec<u8> = Vec::new();
+        png.write_to(&mut out).unwrap();
+        assert_eq!(out, png.as_bytes());
+    }
+
+    #[test]
+    fn test_as_bytes_reuses_source_until_modified() {
+        assert!(Png::try_from(&PNG_FILE[..]).unwrap().source.is_none());
+        let options = ParseOptions {
+            cache_source: true,
+            ..ParseOptions::default()
+        };
+        let mut png = Png::parse_with_options(&PNG_FILE[..], &options).unwrap();
+        assert!(png.source.is_some());
+        assert_eq!(png.as_bytes(), PNG_FILE.to_vec());
+
+        let iend = png.chunks().len() - 1;
+        png.insert_chunk(iend, chunk_from_strings("ruSt", "new").unwrap()).unwrap();
+        assert!(png.source.is_none());
+        let bytes = png.as_bytes();
+        assert_eq!(bytes.len(), PNG_FILE.len() + REQ_FIELDS_WIDTH + 3);
+        assert_eq!(Png::try_from(bytes.as_slice()).unwrap(), png);
+
+        let mut png = Png::parse_with_options(&PNG_FILE[..], &options).unwrap();
+        png.retag_chunk(5, ChunkType::from_str("ruSt").unwrap()).unwrap();
+        assert_ne!(png.as_bytes(), PNG_FILE.to_vec());
+    }
+
+    #[test]
+    fn test_hexdump() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let mut out: Vec<u8> = Vec::new();
+        png.hexdump(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), PNG_FILE.len().div_ceil(16));
+        assert_eq!(
+            lines[0],
+            "00000000  89 50 4e 47 0d 0a 1a 0a  00 00 00 0d 49 48 44 52  \
+             |.PNG........IHDR|  signature, IHDR @8"
+        );
+        assert!(lines[1].ends_with('|'));
+        assert!(lines[2].ends_with("sRGB @21, gAMA @2e"));
+        assert!(out.contains("IEND @"));
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let hex = png.to_hex();
+        assert!(hex.starts_with("89504e470d0a1a0a"));
+        assert_eq!(Png::from_hex(&hex).unwrap().as_bytes(), PNG_FILE.to_vec());
+    }
+
+    #[test]
+    fn test_from_hex_ignores_whitespace() {
+        let hex = testing_png().to_hex();
+        let spaced: String = hex
+            .as_bytes()
+            .chunks(32)
+            .map(|line| format!("  {}\n", std::str::from_utf8(line).unwrap()))
+            .collect();
+        assert_eq!(Png::from_hex(&spaced).unwrap().to_hex(), hex);
+    }
+
+    #[test]
+    fn test_from_hex_invalid() {
+        assert!(Png::from_hex("89504e470d0a1a0").is_err());
+        assert!(Png::from_hex("89504e470d0a1a0z").is_err());
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn test_base64_round_trip() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let encoded = png.to_base64();
+        assert!(encoded.starts_with("iVBORw0KGgo"));
+        assert_eq!(Png::from_base64(&encoded).unwrap().as_bytes(), PNG_FILE.to_vec());
+    }
+
+    #[test]
+    fn test_ihdr() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let ihdr = png.ihdr().unwrap();
+        assert_eq!(ihdr.width(), 50);
+        assert_eq!(ihdr.height(), 50);
+        assert_eq!(ihdr.bit_depth(), 8);
+    }
+
+    #[test]
+    fn test_ihdr_missing() {
+        let png = testing_png();
+        assert!(png.ihdr().is_err());
+        assert!(Png::from_chunks(Vec::new()).ihdr().is_err());
+    }
+
+    #[test]
+    fn test_iend() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert_eq!(png.iend().unwrap().length(), 0);
+        assert!(testing_png().iend().is_none());
+    }
+
+    #[test]
+    fn test_indices_of_type() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("miDl", "I am a repeat").unwrap());
+        png.append_chunk(chunk_from_strings("LASt", "I am in the way").unwrap());
+        png.append_chunk(chunk_from_strings("miDl", "I am another repeat").unwrap());
+        assert_eq!(png.indices_of_type("miDl"), [1, 3, 5]);
+        assert!(png.indices_of_type("NoNe").is_empty());
+    }
+
+    #[test]
+    fn test_ancillary_byte_count() {
+        // sRGB, gAMA and pHYs chunks with 1, 4 and 9 bytes of data
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert_eq!(png.ancillary_byte_count(), 3 * REQ_FIELDS_WIDTH + 14);
+
+        let png = png_from_types(&["IHDR", "IDAT", "IEND"]);
+        assert_eq!(png.ancillary_byte_count(), 0);
+    }
+
+    #[test]
+    fn test_index_by_type() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("miDl", "I am a repeat").unwrap());
+        let index = png.index_by_type();
+        assert_eq!(index.len(), 3);
+        assert_eq!(index[&ChunkType::from_str("miDl").unwrap()], [1, 3]);
+        assert_eq!(index[&ChunkType::from_str("LASt").unwrap()], [2]);
+    }
+
+    #[test]
+    fn test_check_file_signature() {
+        assert!(check_file_signature(&PNG_FILE).is_ok());
+        let message = |bytes: &[u8]| check_file_signature(bytes).unwrap_err().to_string();
+        assert_eq!(
+            message(&[0xff, 0xd8, 0xff, 0xe0, 0, 0x10, b'J', b'F']),
+            "this looks like a JPEG, not a PNG"
+        );
+        assert_eq!(message(b"GIF89a\x01\0"), "this looks like a GIF, not a PNG");
+        assert_eq!(
+            message(b"RIFF\0\0\0\0WEBPVP8 "),
+            "this looks like a WebP, not a PNG"
+        );
+        assert_eq!(message(b"hello, world"), "not a PNG file");
+        assert_eq!(message(b""), "not a PNG file");
+    }
+
+    #[test]
+    fn test_save_and_from_file() {
+        let path = std::env::temp_dir().join(format!("pngme-test-{}.png", std::process::id()));
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.save(&path).unwrap();
+        let loaded = Png::from_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), png);
+
+        assert!(Png::from_file(&path).is_err());
+        assert!(png.save(std::env::temp_dir().join("missing-dir/out.png")).is_err());
+    }
+
+    #[test]
+    fn test_from_file_wrong_format() {
+        let path = std::env::temp_dir().join(format!("pngme-test-{}.gif", std::process::id()));
+        fs::write(&path, b"GIF87a\x01\0\x01\0").unwrap();
+        let err = Png::from_file(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(err.to_string(), "this looks like a GIF, not a PNG");
+    }
+
+    #[test]
+    fn test_try_from_lenient_start() {
+        let mut bytes = vec![0xab; 16];
+        bytes.extend(PNG_FILE);
+        assert!(Png::try_from(bytes.as_slice()).is_err());
+        let (png, offset) = Png::try_from_lenient_start(&bytes).unwrap();
+        assert_eq!(offset, 16);
+        assert_eq!(png, Png::try_from(&PNG_FILE[..]).unwrap());
+
+        let (_, offset) = Png::try_from_lenient_start(&PNG_FILE).unwrap();
+        assert_eq!(offset, 0);
+    }
+
+    #[test]
+    fn test_try_from_lenient_start_outside_window() {
+        let mut bytes = vec![0; Png::LENIENT_START_WINDOW + 1];
+        bytes.extend(PNG_FILE);
+        assert!(crate::error::is_invalid_signature(
+            &Png::try_from_lenient_start(&bytes).unwrap_err()
+        ));
+    }
+
+    #[test]
+    fn test_png_ref() {
+        let png_ref = PngRef::try_from(&PNG_FILE[..]).unwrap();
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert_eq!(png_ref.chunks().len(), png.chunks().len());
+        let ihdr = png_ref.chunk_by_type("IHDR").unwrap();
+        assert_eq!(ihdr.data(), png.chunk_by_type("IHDR").unwrap().data());
+        // the data is a view into the original bytes
+        assert!(PNG_FILE.as_ptr_range().contains(&ihdr.data().as_ptr()));
+        assert!(png_ref.chunk_by_type("zTXt").is_none());
+        assert_eq!(png_ref.to_owned(), png);
+        assert_eq!(png_ref.to_owned().as_bytes(), PNG_FILE.to_vec());
+
+        assert!(PngRef::try_from(&PNG_FILE[1..]).is_err());
+        assert!(PngRef::try_from(&PNG_FILE[..PNG_FILE.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_parse_with_options() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let parsed = Png::parse_with_options(&PNG_FILE[..], &ParseOptions::default()).unwrap();
+        assert_eq!(parsed, png);
+
+        // the test file has a RuSt chunk, which is critical but not standard
+        let err = Png::parse_with_options(&PNG_FILE[..], &ParseOptions::strict()).unwrap_err();
+        assert_eq!(err.to_string(), "unknown critical chunk RuSt at index 5");
+
+        let small = ParseOptions {
+            max_chunk_size: Some(4),
+            ..ParseOptions::default()
+        };
+        let err = Png::parse_with_options(&PNG_FILE[..], &small).unwrap_err();
+        assert_eq!(err.to_string(), "chunk at index 0 is too large: 13 bytes (max 4)");
+
+        let mut bytes = Png::STANDARD_HEADER.to_vec();
+        let mut corrupt = chunk_from_strings("ruxt", "note").unwrap().as_bytes();
+        let crc_begin = corrupt.len() - CRC_WIDTH;
+        corrupt[crc_begin] ^= 1;
+        bytes.extend(corrupt);
+        bytes.extend(Chunk::new_iend().as_bytes());
+        assert!(crate::error::is_crc_mismatch(&Png::try_from(bytes.as_slice()).unwrap_err()));
+        let parsed = Png::parse_with_options(&bytes, &ParseOptions::fast()).unwrap();
+        assert_eq!(parsed.chunks().len(), 2);
+        assert!(Png::try_from(&parsed.as_bytes()[..]).is_ok());
+        let cached = ParseOptions {
+            cache_source: true,
+            ..ParseOptions::fast()
+        };
+        let parsed = Png::parse_with_options(&bytes, &cached).unwrap();
+        assert!(parsed.source.is_none());
+        assert!(Png::try_from(&parsed.as_bytes()[..]).is_ok());
+        let parsed = Png::parse_with_options(&PNG_FILE[..], &cached).unwrap();
+        assert!(parsed.source.is_some());
+        let lenient = ParseOptions {
+            verify_crcs: false,
+            ..ParseOptions::strict()
+        };
+        let err = Png::parse_with_options(&bytes, &lenient).unwrap_err();
+        assert_eq!(err.to_string(), "ruxt chunk at index 0 has an invalid reserved bit");
+    }
+
+    #[test]
+    fn test_from_bytes_lossy() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let (recovered, log) = Png::from_bytes_lossy(&PNG_FILE[..]);
+        assert_eq!(recovered, png);
+        assert!(log.is_empty());
+
+        // flip a bit in the data of the third chunk, and add some junk
+        // between chunks and after IEND
+        let range = png.byte_range(2).unwrap();
+        let mut bytes = PNG_FILE.to_vec();
+        bytes[range.start + 9] ^= 1;
+        let range_after = png.byte_range(4).unwrap();
+        bytes.splice(range_after.start..range_after.start, *b"junk");
+        bytes.extend(b"xyz");
+
+        let (recovered, log) = Png::from_bytes_lossy(&bytes);
+        let mut expected: Vec<&ChunkType> = png.iter().map(Chunk::chunk_type).collect();
+        expected.remove(2);
+        let actual: Vec<&ChunkType> = recovered.iter().map(Chunk::chunk_type).collect();
+        assert_eq!(actual, expected);
+        assert_eq!(recovered.chunks()[2], png.chunks()[3]);
+        assert_eq!(recovered.trailer(), b"xyz");
+        assert_eq!(
+            log,
+            [
+                format!(
+                    "skipped {} byte(s) at offset {}: checksum does not match data",
+                    range.len(),
+                    range.start
+                ),
+                format!(
+                    "skipped 4 byte(s) at offset {}: invalid png (incomplete chunk)",
+                    range_after.start
+                ),
+                "kept 3 byte(s) after IEND as a trailer".to_owned(),
+            ]
+        );
+
+        let (recovered, log) = Png::from_bytes_lossy(&PNG_FILE[8..PNG_FILE.len() - 1]);
+        assert_eq!(recovered.chunks().len(), png.chunks().len() - 1);
+        assert_eq!(log[0], "missing PNG signature");
+        assert_eq!(log.len(), 2);
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let png = Png::from_reader(&PNG_FILE[..]).unwrap();
+        assert_eq!(png, Png::try_from(&PNG_FILE[..]).unwrap());
+
+        let truncated = &PNG_FILE[..PNG_FILE.len() - 2];
+        assert!(Png::from_reader(truncated).is_err());
+        assert!(Png::from_reader(&PNG_FILE[..8]).is_err());
+        assert!(crate::error::is_invalid_signature(
+            &Png::from_reader(&PNG_FILE[1..]).unwrap_err()
+        ));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_async_round_trip() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let mut png = Png::from_async_reader(&PNG_FILE[..]).await.unwrap();
+            assert_eq!(png, Png::try_from(&PNG_FILE[..]).unwrap());
+            png.append_chunk(chunk_from_strings("ruSt", "new").unwrap());
+
+            let mut out: Vec<u8> = Vec::new();
+            png.write_to_async(&mut out).await.unwrap();
+            assert_eq!(out, png.as_bytes());
+
+            let truncated = &PNG_FILE[..PNG_FILE.len() - 2];
+            assert!(Png::from_async_reader(truncated).await.is_err());
+            let err = Png::from_async_reader(&PNG_FILE[1..]).await.unwrap_err();
+            assert!(crate::error::is_invalid_signature(&err));
+        });
+    }
+
+    #[test]
+    fn test_for_each_chunk() {
+        let mut count = 0;
+        Png::for_each_chunk(&mut &PNG_FILE[..], |_| {
+            count += 1;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(count, 7);
+
+        // an error from the callback stops the scan
+        let mut seen = Vec::new();
+        let result = Png::for_each_chunk(&mut &PNG_FILE[..], |chunk| {
+            seen.push(chunk.chunk_type().to_string());
+            assert_or_err(chunk.chunk_type().to_string() != "gAMA", "found gAMA")
+        });
+        assert!(result.is_err());
+        assert_eq!(seen, ["IHDR", "sRGB", "gAMA"]);
+    }
+
+    #[test]
+    fn test_for_each_chunk_stops_after_iend() {
+        let mut bytes = PNG_FILE.to_vec();
+        bytes.extend(b"appended data");
+        let mut count = 0;
+        Png::for_each_chunk(&mut bytes.as_slice(), |_| {
+            count += 1;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(count, 7);
+    }
+
+    #[test]
+    fn test_chunk_iter() {
+        let types: Vec<String> = Png::chunk_iter(&PNG_FILE[..])
+            .map(|chunk| chunk.unwrap())
+            .take_while(|chunk| chunk.chunk_type().to_string() != "IDAT")
+            .map(|chunk| chunk.chunk_type().to_string())
+            .collect();
+        assert_eq!(types, ["IHDR", "sRGB", "gAMA", "pHYs"]);
+
+        // nothing past IEND is read
+        let mut bytes = PNG_FILE.to_vec();
+        bytes.extend(b"trailing garbage");
+        assert_eq!(Png::chunk_iter(bytes.as_slice()).count(), 7);
+    }
+
+    #[test]
+    fn test_chunk_iter_errors() {
+        let mut chunks = Png::chunk_iter(&PNG_FILE[1..]);
+        assert!(chunks.next().unwrap().is_err());
+        assert!(chunks.next().is_none());
+
+        let truncated = &PNG_FILE[..PNG_FILE.len() - 2];
+        let results: Vec<Result<Chunk>> = Png::chunk_iter(truncated).collect();
+        assert_eq!(results.len(), 7);
+        assert!(results.last().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_for_each_chunk_incomplete() {
+        let truncated = &PNG_FILE[..PNG_FILE.len() - 2];
+        assert!(Png::for_each_chunk(&mut &truncated[..], |_| Ok(())).is_err());
+        assert!(Png::for_each_chunk(&mut &PNG_FILE[1..], |_| Ok(())).is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert!(png.validate().is_ok());
+    }
+
+    /// Build a Png with the given chunk types (with empty data)
+    fn png_from_types(types: &[&str]) -> Png {
+        types
+            .iter()
+            .map(|t| chunk_from_strings(t, "").unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_chunk_ordering() {
+        let png = png_from_types(&[
+            "IHDR", "gAMA", "PLTE", "bKGD", "pHYs", "IDAT", "tIME", "IEND",
+        ]);
+        assert!(png.validate_chunk_ordering().is_ok());
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert!(png.validate_chunk_ordering().is_ok());
+    }
+
+    #[test]
+    fn test_validate_chunk_ordering_after_idat() {
+        let png = png_from_types(&["IHDR", "PLTE", "IDAT", "bKGD", "IEND"]);
+        assert_eq!(
+            png.validate_chunk_ordering().unwrap_err().to_string(),
+            "bKGD chunk at index 3 must come before IDAT"
+        );
+        assert!(png.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_chunk_ordering_plte() {
+        let png = png_from_types(&["IHDR", "PLTE", "gAMA", "IDAT", "IEND"]);
+        assert_eq!(
+            png.validate_chunk_ordering().unwrap_err().to_string(),
+            "gAMA chunk at index 2 must come before PLTE"
+        );
+        let png = png_from_types(&["IHDR", "tRNS", "PLTE", "IDAT", "IEND"]);
+        assert_eq!(
+            png.validate_chunk_ordering().unwrap_err().to_string(),
+            "tRNS chunk at index 1 must come after PLTE"
+        );
+    }
+
+    #[test]
+    fn test_check_ordering() {
+        let png = png_from_types(&[
+            "IHDR", "gAMA", "PLTE", "bKGD", "pHYs", "IDAT", "IDAT", "tIME", "IEND",
+        ]);
+        assert!(png.check_ordering().is_empty());
+        assert!(Png::try_from(&PNG_FILE[..]).unwrap().check_ordering().is_empty());
+
+        let png = png_from_types(&[
+            "gAMA", "IHDR", "IDAT", "tEXt", "IDAT", "PLTE", "IEND", "pHYs",
+        ]);
+        assert_eq!(
+            png.check_ordering(),
+            [
+                "IHDR chunk must be the first chunk",
+                "IEND chunk must be the last chunk",
+                "PLTE chunk at index 5 must come before IDAT",
+                "IDAT chunk at index 4 must directly follow the previous IDAT chunk",
+                "pHYs chunk at index 7 must come before IDAT",
+            ]
+        );
+        assert_eq!(png_from_types(&["IHDR", "IDAT"]).check_ordering(), ["no IEND chunk"]);
+    }
+
+    #[test]
+    fn test_check_singletons() {
+        assert!(Png::try_from(&PNG_FILE[..]).unwrap().check_singletons().is_empty());
+        let png = png_from_types(&[
+            "IHDR", "tIME", "PLTE", "IDAT", "IDAT", "tEXt", "tEXt", "tIME", "IEND", "IEND",
+        ]);
+        assert_eq!(
+            png.check_singletons(),
+            ["multiple IEND chunks found", "multiple tIME chunks found"]
+        );
+    }
+
+    #[test]
+    fn test_append_and_insert_chunk_unique() {
+        let mut png = png_from_types(&["IHDR", "IDAT", "IEND"]);
+        png.append_chunk_unique(chunk_from_strings("tEXt", "a").unwrap()).unwrap();
+        png.append_chunk_unique(chunk_from_strings("tEXt", "b").unwrap()).unwrap();
+        png.insert_chunk_unique(1, chunk_from_strings("tIME", "").unwrap()).unwrap();
+
+        let err = png
+            .insert_chunk_unique(1, chunk_from_strings("tIME", "").unwrap())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "png already has a tIME chunk");
+        assert!(png.append_chunk_unique(Chunk::new_iend()).is_err());
+        assert_eq!(png.chunks().len(), 6);
+    }
+
+    #[test]
+    fn test_diagnose() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let codes: Vec<DiagnosticCode> = png.diagnose().iter().map(|d| d.code).collect();
+        // the test file's RuSt chunk is critical but not a standard type
+        assert_eq!(codes, [DiagnosticCode::UnknownCritical]);
+
+        let mut bytes = chunk_from_strings("gAMA", "data").unwrap().as_bytes();
+        let crc_begin = bytes.len() - CRC_WIDTH;
+        bytes[crc_begin] ^= 1;
+        let chunks = vec![
+            ImageHeader::new(1, 1, 8, TRUECOLOR_ALPHA).unwrap().to_chunk(),
+            Chunk::try_from_unverified(&bytes).unwrap(),
+            chunk_from_strings("IDAT", "").unwrap(),
+            chunk_from_strings("tEXt", "no separator").unwrap(),
+            chunk_from_strings("gAMA", "").unwrap(),
+            chunk_from_strings("ruxt", "").unwrap(),
+            Chunk::new_iend(),
         ];
+        let png = Png::from_parts(chunks, b"extra".to_vec());
+        let report = png.diagnose();
+        let summary: Vec<(Severity, DiagnosticCode, Option<usize>)> = report
+            .iter()
+            .map(|d| (d.severity, d.code, d.chunk_index))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (Severity::Error, DiagnosticCode::CrcMismatch, Some(1)),
+                (Severity::Error, DiagnosticCode::BadKeyword, Some(3)),
+                (Severity::Error, DiagnosticCode::InvalidReservedBit, Some(5)),
+                (Severity::Error, DiagnosticCode::Ordering, Some(4)),
+                (Severity::Error, DiagnosticCode::DuplicateSingleton, Some(4)),
+                (Severity::Warning, DiagnosticCode::TrailingData, None),
+            ]
+        );
+        assert_eq!(report[0].offset, png.byte_range(1).map(|r| r.start));
+        assert_eq!(report[3].message, "gAMA chunk at index 4 must come before IDAT");
+        assert_eq!(report[5].offset, Some(png.total_len() - 5));
+        assert!(png.validate().is_err());
+    }
 
-        chunk_bytes.append(&mut bad_chunk);
+    #[test]
+    fn test_copy_ancillary_from() {
+        let source = png_from_types(&[
+            "IHDR", "gAMA", "PLTE", "tRNS", "pHYs", "IDAT", "tEXt", "tIME", "IEND",
+        ]);
+        let mut target = png_from_types(&["IHDR", "PLTE", "IDAT", "IDAT", "tIME", "IEND"]);
+        let log = target.copy_ancillary_from(&source, |_| true);
+        assert_eq!(
+            log,
+            [
+                "copied gAMA chunk to index 1",
+                "copied tRNS chunk to index 3",
+                "copied pHYs chunk to index 4",
+                "copied tEXt chunk to index 8",
+                "skipped tIME chunk: png already has a tIME chunk",
+            ]
+        );
+        let types: Vec<String> = target.iter().map(|c| c.chunk_type().to_string()).collect();
+        assert_eq!(
+            types,
+            ["IHDR", "gAMA", "PLTE", "tRNS", "pHYs", "IDAT", "IDAT", "tIME", "tEXt", "IEND"]
+        );
+        assert!(target.check_ordering().is_empty());
+    }
 
-        let png = Png::try_from(chunk_bytes.as_ref());
+    #[test]
+    fn test_copy_ancillary_from_unsafe_and_filter() {
+        let mut source = png_from_types(&["IHDR", "gAMA", "IDAT", "tEXt", "iTXt", "IEND"]);
+        source.chunks[0] = ImageHeader::new(2, 2, 8, TRUECOLOR_ALPHA).unwrap().to_chunk();
+        let mut target = Png::default();
+        let log = target.copy_ancillary_from(&source, |c| c.chunk_type().to_string() != "iTXt");
+        assert_eq!(
+            log,
+            [
+                "skipped unsafe-to-copy gAMA chunk, as the critical chunks differ",
+                "copied tEXt chunk to index 2",
+            ]
+        );
+        assert_eq!(target.chunks().len(), 4);
+    }
 
-        assert!(png.is_err());
+    #[test]
+    fn test_ensure_iend() {
+        let mut png = testing_png();
+        assert!(png.ensure_iend().is_err());
+        png.append_chunk(Chunk::new_iend());
+        assert!(png.ensure_iend().is_ok());
     }
 
     #[test]
-    fn test_list_chunks() {
-        let png = testing_png();
-        let chunks = png.chunks();
-        assert_eq!(chunks.len(), 3);
+    fn test_validate_single_ihdr_and_iend() {
+        let png = png_from_types(&["IHDR", "PLTE", "IDAT", "IEND"]);
+        assert!(png.validate_single_ihdr_and_iend().is_ok());
+
+        let png = png_from_types(&["IHDR", "IHDR", "IDAT", "IEND"]);
+        assert_eq!(
+            png.validate().unwrap_err().to_string(),
+            "multiple IHDR chunks found"
+        );
+        let png = png_from_types(&["IHDR", "PLTE", "PLTE", "IDAT", "IEND"]);
+        assert_eq!(
+            png.validate().unwrap_err().to_string(),
+            "multiple PLTE chunks found"
+        );
+        let png = png_from_types(&["IHDR", "IDAT", "IEND", "IEND"]);
+        assert_eq!(
+            png.validate().unwrap_err().to_string(),
+            "multiple IEND chunks found"
+        );
     }
 
     #[test]
-    fn test_chunk_by_type() {
-        let png = testing_png();
-        let chunk = png.chunk_by_type("FrSt").unwrap();
-        assert_eq!(&chunk.chunk_type().to_string(), "FrSt");
-        assert_eq!(&chunk.data_as_string().unwrap(), "I am the first chunk");
+    fn test_validate_missing_or_misplaced_ihdr() {
+        let png = png_from_types(&["IDAT", "IEND"]);
+        assert_eq!(
+            png.validate().unwrap_err().to_string(),
+            "invalid png (missing IHDR)"
+        );
+        let png = png_from_types(&["tEXt", "IHDR", "IDAT", "IEND"]);
+        assert_eq!(
+            png.validate().unwrap_err().to_string(),
+            "IHDR chunk must be the first chunk"
+        );
     }
 
     #[test]
-    fn test_append_chunk() {
+    fn test_validate_missing_or_misplaced_iend() {
+        let png = png_from_types(&["IHDR", "IDAT"]);
+        assert_eq!(
+            png.validate_single_ihdr_and_iend().unwrap_err().to_string(),
+            "no IEND chunk"
+        );
+        let png = png_from_types(&["IHDR", "IDAT", "IEND", "tEXt"]);
+        assert_eq!(
+            png.validate().unwrap_err().to_string(),
+            "IEND chunk must be the last chunk"
+        );
+    }
+
+    #[test]
+    fn test_validate_non_empty_iend() {
+        let mut png = png_from_types(&["IHDR", "IDAT"]);
+        png.append_chunk(chunk_from_strings("IEND", "oops").unwrap());
+        assert!(png.ensure_iend().is_err());
+        assert_eq!(
+            png.validate().unwrap_err().to_string(),
+            "IEND chunk must not contain data"
+        );
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_compress_text_chunks_round_trip() {
+        let long = TextChunk::new("Comment", &"Compress me! ".repeat(20)).unwrap();
+        let short = TextChunk::new("Title", "Tiny").unwrap();
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.extend([long.to_chunk(), short.to_chunk()]);
+
+        assert_eq!(png.compress_text_chunks(64).unwrap(), 1);
+        assert!(png.chunk_by_type("zTXt").is_some());
+        assert_eq!(png.indices_of_type("tEXt").len(), 1);
+
+        assert_eq!(png.decompress_text_chunks().unwrap(), 1);
+        let texts: Vec<TextChunk> = png
+            .chunks()
+            .iter()
+            .filter(|c| c.chunk_type().to_string() == "tEXt")
+            .map(|c| TextChunk::try_from(c).unwrap())
+            .collect();
+        assert_eq!(texts, [long, short]);
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_decompress_text_chunks_unchanged_on_error() {
+        let valid = TextChunk::new("Comment", "Squeeze me").unwrap();
+        let mut corrupt = b"Title\0\0".to_vec();
+        corrupt.extend(b"not zlib data");
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.extend([
+            valid.to_compressed_chunk().unwrap(),
+            Chunk::new(ChunkType::from_str("zTXt").unwrap(), corrupt),
+        ]);
+        let before = png.as_bytes();
+
+        assert!(png.decompress_text_chunks().is_err());
+        assert_eq!(png.as_bytes(), before);
+        assert_eq!(png.indices_of_type("zTXt").len(), 2);
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_compress_text_chunks_unchanged_on_error() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.extend([
+            TextChunk::new("Comment", "Squeeze me").unwrap().to_chunk(),
+            chunk_from_strings("tEXt", "no separator").unwrap(),
+        ]);
+        let before = png.as_bytes();
+
+        assert!(png.compress_text_chunks(0).is_err());
+        assert_eq!(png.as_bytes(), before);
+    }
+
+    #[test]
+    fn test_metadata_eq() {
+        let title = TextChunk::new("Title", "Dice").unwrap();
+        let author = TextChunk::new("Author", "Wikipedia").unwrap();
+
+        let mut first = Png::try_from(&PNG_FILE[..]).unwrap();
+        first.extend([title.to_chunk(), author.to_chunk()]);
+        let mut second = Png::try_from(&PNG_FILE[..]).unwrap();
+        second.extend([author.to_chunk(), title.to_chunk()]);
+
+        assert!(first.metadata_eq(&second));
+        assert!(first != second);
+    }
+
+    #[test]
+    fn test_metadata_eq_differences() {
+        let title = TextChunk::new("Title", "Dice").unwrap();
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+
+        let mut extra = Png::try_from(&PNG_FILE[..]).unwrap();
+        extra.extend([title.to_chunk()]);
+        assert!(!png.metadata_eq(&extra));
+        assert!(!extra.metadata_eq(&png));
+
+        let mut swapped_critical = Png::try_from(&PNG_FILE[..]).unwrap();
+        swapped_critical.chunks.swap(0, 4);
+        assert!(!png.metadata_eq(&swapped_critical));
+    }
+
+    #[test]
+    fn test_crc_write_modes() {
+        let mut bytes = PNG_FILE.to_vec();
+        // corrupt the checksum of the IHDR chunk
+        bytes[29] ^= 0xFF;
+        assert!(Png::try_from(bytes.as_slice()).is_err());
+
+        let chunks: Vec<Chunk> = vec![
+            Chunk::try_from_unverified(&bytes[8..33]).unwrap(),
+            Chunk::try_from(&bytes[PNG_FILE.len() - 12..]).unwrap(),
+        ];
+        let png = Png::from_chunks(chunks);
+
+        let mut preserved: Vec<u8> = Vec::new();
+        png.write_to_preserving_crcs(&mut preserved).unwrap();
+        assert_eq!(preserved[8..33], bytes[8..33]);
+
+        let recomputed = png.as_bytes();
+        assert_eq!(recomputed[8..33], PNG_FILE[8..33]);
+    }
+
+    #[test]
+    fn test_hide_note() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let first = png.hide_note("first note").unwrap();
+        let second = png.hide_note("second note").unwrap();
+
+        assert_eq!(&first.to_string(), "ruSt");
+        assert_eq!(&second.to_string(), "rvSt");
+        for chunk_type in [first, second] {
+            assert!(!chunk_type.is_critical());
+            assert!(!chunk_type.is_public());
+            assert!(chunk_type.is_reserved_bit_valid());
+            assert!(chunk_type.is_safe_to_copy());
+        }
+        assert_eq!(
+            png.chunk_by_type("rvSt").unwrap().data_as_string().unwrap(),
+            "second note"
+        );
+        assert!(png.validate().is_ok());
+    }
+
+    #[test]
+    fn test_with_capacity() {
+        let png = Png::with_capacity(10);
+        assert!(png.capacity() >= 10);
+        assert!(png.chunks().is_empty());
+    }
+
+    #[test]
+    fn test_reserve() {
         let mut png = testing_png();
-        png.append_chunk(chunk_from_strings("TeSt", "Message").unwrap());
-        let chunk = png.chunk_by_type("TeSt").unwrap();
-        assert_eq!(&chunk.chunk_type().to_string(), "TeSt");
-        assert_eq!(&chunk.data_as_string().unwrap(), "Message");
+        png.reserve(100);
+        let capacity = png.capacity();
+        assert!(capacity >= 103);
+        for i in 0..100 {
+            png.append_chunk(chunk_from_strings("TeSt", &i.to_string()).unwrap());
+        }
+        assert_eq!(png.capacity(), capacity);
+    }
+
+    /// Reader that counts how many bytes were actually read
+    struct CountingReader<R> {
+        inner: R,
+        bytes_read: usize,
+    }
+
+    impl<R: Read> Read for CountingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let read = self.inner.read(buf)?;
+            self.bytes_read += read;
+            Ok(read)
+        }
+    }
+
+    impl<R: Seek> Seek for CountingReader<R> {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.inner.seek(pos)
+        }
     }
 
     #[test]
-    fn test_remove_chunk() {
+    fn test_index_file() {
+        let mut reader = CountingReader {
+            inner: io::Cursor::new(&PNG_FILE[..]),
+            bytes_read: 0,
+        };
+        let entries = Png::index_file(&mut reader).unwrap();
+
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert_eq!(entries.len(), png.chunks().len());
+        let mut offset = 8;
+        for (entry, chunk) in entries.iter().zip(png.chunks()) {
+            assert_eq!(entry.offset, offset);
+            assert_eq!(&entry.chunk_type, chunk.chunk_type());
+            assert_eq!(entry.length, chunk.length());
+            assert_eq!(entry.crc, chunk.crc());
+            offset += (REQ_FIELDS_WIDTH as u64) + u64::from(chunk.length());
+        }
+
+        // only the signature and the length/type/CRC fields should have been read
+        assert_eq!(reader.bytes_read, 8 + 12 * entries.len());
+    }
+
+    #[test]
+    fn test_index_file_stops_at_iend() {
+        let mut bytes = PNG_FILE.to_vec();
+        bytes.extend(b"trailing junk");
+        let entries = Png::index_file(&mut io::Cursor::new(&bytes)).unwrap();
+        assert_eq!(entries.len(), Png::try_from(&PNG_FILE[..]).unwrap().chunks().len());
+    }
+
+    #[test]
+    fn test_index_file_truncated() {
+        let mut reader = io::Cursor::new(&PNG_FILE[..PNG_FILE.len() - 1]);
+        assert!(Png::index_file(&mut reader).is_err());
+
+        let mut reader = io::Cursor::new(&PNG_FILE[1..]);
+        assert!(Png::index_file(&mut reader).is_err());
+    }
+
+    #[test]
+    fn test_dedup_ancillary() {
+        let title = TextChunk::new("Title", "Dice").unwrap();
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.extend([
+            title.to_chunk(),
+            TextChunk::new("Title", "Not a duplicate").unwrap().to_chunk(),
+            title.to_chunk(),
+        ]);
+
+        assert_eq!(png.dedup_ancillary(), 1);
+        assert_eq!(png.indices_of_type("tEXt").len(), 2);
+        assert_eq!(png.dedup_ancillary(), 0);
+    }
+
+    #[test]
+    fn test_dedup_ancillary_keeps_critical() {
+        let mut png = png_from_types(&["IHDR", "IDAT", "IDAT", "IEND"]);
+        assert_eq!(png.dedup_ancillary(), 0);
+        assert_eq!(png.chunks().len(), 4);
+    }
+
+    #[test]
+    fn test_truncate_to_iend() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.append_chunk(chunk_from_strings("TrAl", "tracking").unwrap());
+        png.append_chunk(Chunk::new_iend());
+
+        assert_eq!(png.truncate_to_iend(), 20 + 12);
+        assert_eq!(png.as_bytes(), PNG_FILE.to_vec());
+        assert_eq!(png.truncate_to_iend(), 0);
+    }
+
+    #[test]
+    fn test_truncate_to_iend_parsed_trailer() {
+        let mut bytes = PNG_FILE.to_vec();
+        bytes.extend(chunk_from_strings("TrAl", "tracking").unwrap().as_bytes());
+        bytes.extend(b"junk");
+        let mut png = Png::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(png.trailer().len(), 24);
+
+        assert_eq!(png.truncate_to_iend(), 24);
+        assert!(png.trailer().is_empty());
+        assert_eq!(png.as_bytes(), PNG_FILE.to_vec());
+    }
+
+    #[test]
+    fn test_truncate_to_iend_without_iend() {
         let mut png = testing_png();
-        png.append_chunk(chunk_from_strings("TeSt", "Message").unwrap());
-        png.remove_chunk("TeSt").unwrap();
-        let chunk = png.chunk_by_type("TeSt");
-        assert!(chunk.is_none());
+        assert_eq!(png.truncate_to_iend(), 0);
+        assert_eq!(png.chunks().len(), 3);
+    }
+
+    #[test]
+    fn test_repair() {
+        let mut png = png_from_types(&["IHDR", "IDAT", "ruxt"]);
+        let log = png.repair();
+        assert_eq!(
+            log,
+            [
+                "renamed ruxt chunk at index 2 to ruXt",
+                "added missing IEND chunk",
+            ]
+        );
+        assert_eq!(&png.chunks()[2].chunk_type().to_string(), "ruXt");
+        assert!(png.validate().is_ok());
+        assert!(png.repair().is_empty());
+    }
+
+    #[test]
+    fn test_repair_bytes() {
+        let (png, log) = Png::repair_bytes(&PNG_FILE[..]).unwrap();
+        assert_eq!(png, Png::try_from(&PNG_FILE[..]).unwrap());
+        assert!(log.is_empty());
+
+        // insert a byte into the gAMA chunk's data without fixing its length
+        // or checksum, as a hex editor would
+        let original = Png::try_from(&PNG_FILE[..]).unwrap();
+        let gama = original.byte_range(2).unwrap();
+        let mut bytes = PNG_FILE.to_vec();
+        bytes.insert(gama.start + LENGTH_WIDTH + TYPE_WIDTH + 1, 0x42);
+        assert!(Png::try_from(bytes.as_slice()).is_err());
+
+        let (png, log) = Png::repair_bytes(&bytes).unwrap();
+        assert_eq!(
+            log,
+            [
+                "rewrote length of gAMA chunk at index 2 from 4 to 5",
+                "recomputed checksum of gAMA chunk at index 2",
+            ]
+        );
+        assert_eq!(png.chunks()[2].data(), [0, 0x42, 0, 177, 143]);
+        assert_eq!(png.chunks().len(), original.chunks().len());
+        assert!(Png::try_from(png.as_bytes().as_slice()).is_ok());
+
+        assert!(Png::repair_bytes(&PNG_FILE[1..]).is_err());
+    }
+
+    #[test]
+    fn test_repair_forced_length() {
+        let mut png = png_from_types(&["IHDR", "IDAT", "IEND"]);
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        let chunk = Chunk::new_unchecked(9, chunk_type, b"note".to_vec(), 0);
+        png.insert_chunk(2, chunk).unwrap();
+        assert!(Png::try_from(png.as_bytes().as_slice()).is_err());
+        let log = png.repair();
+        assert_eq!(
+            log,
+            [
+                "rewrote length of ruSt chunk at index 2 from 9 to 4",
+                "recomputed checksum of ruSt chunk at index 2",
+            ]
+        );
+        assert_eq!(png.chunks()[2].length(), 4);
+        assert!(Png::try_from(png.as_bytes().as_slice()).is_ok());
+    }
+
+    #[test]
+    fn test_repair_crc_and_trailing_chunks() {
+        let mut bytes = chunk_from_strings("RuSt", "note").unwrap().as_bytes();
+        let crc_begin = bytes.len() - CRC_WIDTH;
+        bytes[crc_begin..].copy_from_slice(&1234u32.to_be_bytes());
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.append_chunk(Chunk::try_from_unverified(&bytes).unwrap());
+        let mut stale = Chunk::try_from_unverified(&bytes).unwrap();
+        stale.data_mut().push(b'!');
+        png.chunks.insert(1, stale);
+
+        let log = png.repair();
+        assert_eq!(
+            log,
+            [
+                "recomputed checksum of RuSt chunk at index 1",
+                "recomputed checksum of RuSt chunk at index 8",
+                "removed 16 byte(s) after IEND",
+            ]
+        );
+        assert_eq!(png.chunks()[1].stored_crc(), Some(png.chunks()[1].crc()));
+        assert!(png.validate().is_ok());
+    }
+
+    #[test]
+    fn test_default() {
+        let png = Png::default();
+        assert!(png.validate().is_ok());
+        let header = png.ihdr().unwrap();
+        assert_eq!((header.width(), header.height()), (1, 1));
+        assert_eq!(header.color_type(), TRUECOLOR_ALPHA);
+        assert_eq!(png.indices_of_type("IDAT"), [1]);
+        assert_eq!(Png::try_from(png.as_bytes().as_slice()).unwrap(), png);
+    }
+
+    #[test]
+    fn test_byte_range() {
+        let png = testing_png();
+        let first_len = REQ_FIELDS_WIDTH + "I am the first chunk".len();
+        let second_len = REQ_FIELDS_WIDTH + "I am another chunk".len();
+        let range = png.byte_range(1).unwrap();
+        assert_eq!(range, 8 + first_len..8 + first_len + second_len);
+        assert_eq!(png.as_bytes()[range], png.chunks()[1].as_bytes());
+        assert_eq!(png.byte_range(3), None);
+    }
+
+    #[test]
+    fn test_offsets() {
+        let png = testing_png();
+        let offsets: Vec<usize> = png.offsets().collect();
+        assert_eq!(offsets, [8, 40, 70]);
+        let bytes = png.as_bytes();
+        assert_eq!(&bytes[offsets[1] + 4..offsets[1] + 8], b"miDl");
+    }
+
+    #[test]
+    fn test_chunk_offsets() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let offsets: Vec<usize> = png.offsets().collect();
+        assert_eq!(png.chunk_offsets(), Some(offsets.as_slice()));
+        assert_eq!(Png::from_reader(&PNG_FILE[..]).unwrap().chunk_offsets(), Some(&offsets[..]));
+        assert!(testing_png().chunk_offsets().is_none());
+
+        let mut png = png;
+        png.append_chunk(chunk_from_strings("ruSt", "new").unwrap());
+        assert!(png.chunk_offsets().is_none());
+    }
+
+    #[test]
+    fn test_chunk_offsets_lossy() {
+        let first = chunk_from_strings("IHDR", "header").unwrap().as_bytes();
+        let second = chunk_from_strings("ruSt", "note").unwrap().as_bytes();
+        let mut bytes = Png::STANDARD_HEADER.to_vec();
+        bytes.extend(&first);
+        bytes.extend(b"junk");
+        bytes.extend(&second);
+        bytes.extend(Chunk::new_iend().as_bytes());
+
+        let (png, log) = Png::from_bytes_lossy(&bytes);
+        assert_eq!(log.len(), 1);
+        let second_at = 8 + first.len() + 4;
+        let iend_at = second_at + second.len();
+        assert_eq!(png.chunk_offsets(), Some(&[8, second_at, iend_at][..]));
+        assert_eq!(&bytes[second_at + 4..second_at + 8], b"ruSt");
+        assert_ne!(png.offsets().nth(1), Some(second_at));
+    }
+
+    #[test]
+    fn test_chunk_offsets_lenient_start() {
+        let mut bytes = vec![0u8; 16];
+        bytes.extend(PNG_FILE.iter());
+        let (png, start) = Png::try_from_lenient_start(&bytes).unwrap();
+        assert_eq!(start, 16);
+        assert_eq!(png.chunk_offsets().unwrap()[0], 24);
+        assert_eq!(&bytes[28..32], b"IHDR");
+        let shifted: Vec<usize> = png.offsets().map(|offset| offset + 16).collect();
+        assert_eq!(png.chunk_offsets(), Some(shifted.as_slice()));
+    }
+
+    #[test]
+    fn test_chunk_at_offset() {
+        let png = testing_png();
+        // the second chunk spans bytes 40..70
+        let (i, chunk) = png.chunk_at_offset(55).unwrap();
+        assert_eq!(i, 1);
+        assert_eq!(&chunk.chunk_type().to_string(), "miDl");
+        assert_eq!(png.chunk_at_offset(40).unwrap().0, 1);
+        assert_eq!(png.chunk_at_offset(69).unwrap().0, 1);
+        assert_eq!(png.chunk_at_offset(70).unwrap().0, 2);
+    }
+
+    #[test]
+    fn test_chunk_at_offset_outside_chunks() {
+        let png = testing_png();
+        assert!(png.chunk_at_offset(0).is_none());
+        assert!(png.chunk_at_offset(7).is_none());
+        assert!(png.chunk_at_offset(png.as_bytes().len()).is_none());
     }
 
     #[test]
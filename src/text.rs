@@ -0,0 +1,198 @@
+#[cfg(feature = "compression")]
+use crate::compression;
+use crate::{
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    types::{assert_or_err, error_from, Error, Result},
+};
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    str::FromStr,
+};
+
+// keyword length limits from the PNG spec
+pub const MIN_KEYWORD_LEN: usize = 1;
+pub const MAX_KEYWORD_LEN: usize = 79;
+
+/// Stores the contents of a PNG tEXt chunk
+#[derive(Debug, PartialEq, Eq)]
+pub struct TextChunk {
+    keyword: String,
+    text: String,
+}
+
+impl TextChunk {
+    /// Chunk type code of textual data chunks
+    pub const CHUNK_TYPE: &'static str = "tEXt";
+
+    /// Chunk type code of compressed textual data chunks
+    pub const COMPRESSED_CHUNK_TYPE: &'static str = "zTXt";
+
+    /// Create a new text chunk, validating the keyword and text
+    pub fn new(keyword: &str, text: &str) -> Result<TextChunk> {
+        encode_keyword(keyword)?;
+        // the text is stored as latin-1 too, so make sure it can be
+        latin1_encode(text)?;
+        Ok(TextChunk {
+            keyword: keyword.to_owned(),
+            text: text.to_owned(),
+        })
+    }
+
+    /// Get the keyword of this text chunk
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+
+    /// Get the text of this text chunk
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Build a tEXt chunk from this keyword/text pair
+    pub fn to_chunk(&self) -> Chunk {
+        // `new` already made sure that both fields are encodable
+        let mut data = latin1_encode(&self.keyword).unwrap_or_default();
+        data.push(0);
+        data.extend(latin1_encode(&self.text).unwrap_or_default());
+        let chunk_type = ChunkType::from_str(TextChunk::CHUNK_TYPE)
+            .expect("tEXt is a valid chunk type");
+        Chunk::new(chunk_type, data)
+    }
+
+    /// Build a zTXt chunk from this keyword/text pair
+    #[cfg(feature = "compression")]
+    pub fn to_compressed_chunk(&self) -> Result<Chunk> {
+        let mut data = latin1_encode(&self.keyword)?;
+        // separator, then compression method 0 (zlib)
+        data.extend([0, 0]);
+        data.extend(compression::compress(&latin1_encode(&self.text)?)?);
+        let chunk_type = ChunkType::from_str(TextChunk::COMPRESSED_CHUNK_TYPE)?;
+        Ok(Chunk::new(chunk_type, data))
+    }
+
+    /// Parse a zTXt chunk
+    #[cfg(feature = "compression")]
+    pub fn from_compressed_chunk(chunk: &Chunk) -> Result<TextChunk> {
+        assert_or_err(
+            chunk.chunk_type().to_string() == TextChunk::COMPRESSED_CHUNK_TYPE,
+            "chunk is not a zTXt chunk",
+        )?;
+        let (keyword, rest) = split_keyword(chunk.data())?;
+        let (&method, compressed) = rest
+            .split_first()
+            .ok_or_else(|| error_from("invalid zTXt chunk (missing compression method)"))?;
+        assert_or_err(method == 0, "invalid zTXt chunk (unknown compression method)")?;
+        let text = latin1_decode(&compression::decompress(compressed)?);
+        TextChunk::new(&keyword, &text)
+    }
+}
+
+impl TryFrom<&Chunk> for TextChunk {
+    type Error = Error;
+    /// Gives the ability to parse a tEXt chunk
+    fn try_from(chunk: &Chunk) -> Result<Self> {
+        assert_or_err(
+            chunk.chunk_type().to_string() == TextChunk::CHUNK_TYPE,
+            "chunk is not a tEXt chunk",
+        )?;
+        let (keyword, text) = split_keyword(chunk.data())?;
+        TextChunk::new(&keyword, &latin1_decode(text))
+    }
+}
+
+impl Display for TextChunk {
+    /// Gives the ability to format TextChunk as "keyword: text"
+    /// and Enables ToString
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}: {}", self.keyword, self.text)
+    }
+}
+
+/// Check a keyword (or any similar null-terminated name, like an ICC profile
+/// name) against the spec and encode it as latin-1
+pub(crate) fn encode_keyword(keyword: &str) -> Result<Vec<u8>> {
+    assert_or_err(
+        (MIN_KEYWORD_LEN..=MAX_KEYWORD_LEN).contains(&keyword.chars().count()),
+        "keyword must be between 1 and 79 characters long",
+    )?;
+    assert_or_err(
+        !keyword.contains('\0'),
+        "keyword must not contain a null separator",
+    )?;
+    latin1_encode(keyword)
+}
+
+/// Split text chunk data into its keyword and whatever follows the separator
+pub(crate) fn split_keyword(data: &[u8]) -> Result<(String, &[u8])> {
+    let separator = data
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or_else(|| error_from("invalid text chunk (missing separator)"))?;
+    Ok((latin1_decode(&data[..separator]), &data[separator + 1..]))
+}
+
+/// Decode latin-1 bytes (every byte maps to the matching code point)
+fn latin1_decode(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+/// Encode a string as latin-1, erroring on characters outside of it
+fn latin1_encode(s: &str) -> Result<Vec<u8>> {
+    s.chars()
+        .map(|c| u8::try_from(u32::from(c)).map_err(|_| error_from("text is not valid latin-1")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_chunk_round_trip() {
+        let text = TextChunk::new("Title", "A pretty picture").unwrap();
+        let chunk = text.to_chunk();
+        assert_eq!(&chunk.chunk_type().to_string(), "tEXt");
+        assert_eq!(chunk.data(), b"Title\0A pretty picture");
+        assert_eq!(TextChunk::try_from(&chunk).unwrap(), text);
+    }
+
+    #[test]
+    fn test_text_chunk_latin1() {
+        let text = TextChunk::new("Author", "Zoë").unwrap();
+        let chunk = text.to_chunk();
+        assert_eq!(chunk.data(), b"Author\0Zo\xeb");
+        assert_eq!(TextChunk::try_from(&chunk).unwrap().text(), "Zoë");
+        assert!(TextChunk::new("Author", "日本").is_err());
+    }
+
+    #[test]
+    fn test_text_chunk_invalid_keyword() {
+        assert!(TextChunk::new("", "text").is_err());
+        assert!(TextChunk::new(&"k".repeat(80), "text").is_err());
+        assert!(TextChunk::new("key\0word", "text").is_err());
+    }
+
+    #[test]
+    fn test_text_chunk_missing_separator() {
+        let chunk = Chunk::new(ChunkType::from_str("tEXt").unwrap(), b"Title".to_vec());
+        assert!(TextChunk::try_from(&chunk).is_err());
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_compressed_text_chunk_round_trip() {
+        let text = TextChunk::new("Comment", &"Zoë was here. ".repeat(10)).unwrap();
+        let chunk = text.to_compressed_chunk().unwrap();
+        assert_eq!(&chunk.chunk_type().to_string(), "zTXt");
+        assert!(chunk.data().starts_with(b"Comment\0\0"));
+        assert_eq!(TextChunk::from_compressed_chunk(&chunk).unwrap(), text);
+        assert!(TextChunk::try_from(&chunk).is_err());
+    }
+
+    #[test]
+    fn test_text_chunk_wrong_type() {
+        let chunk = Chunk::new(ChunkType::from_str("zTXt").unwrap(), b"Title\0x".to_vec());
+        assert!(TextChunk::try_from(&chunk).is_err());
+    }
+}